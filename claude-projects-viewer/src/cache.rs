@@ -0,0 +1,170 @@
+use crate::parser::ProjectEntry;
+use crate::types::{MessageText, ParseDiagnostic, ProjectFile};
+use home::home_dir;
+use serde::{Deserialize, Serialize};
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Bumped whenever the cached layout or the extracted text changes, so
+/// entries written by older builds are ignored.
+const CACHE_VERSION: u32 = 10;
+
+/// How long to wait between two sweeps for stale entries.
+const PRUNE_INTERVAL: Duration = Duration::from_secs(24 * 60 * 60);
+/// Empty file whose mtime records the last sweep.
+const PRUNE_MARKER: &str = "last-prune";
+
+/// What is kept on disk for one session file.
+#[derive(Serialize, Deserialize)]
+struct CachedFile {
+    version: u32,
+    path: String,
+    size: u64,
+    /// Modification time as nanoseconds since the Unix epoch.
+    modified: Option<u128>,
+    project_name: String,
+    project_path: String,
+    texts: Vec<MessageText>,
+    diagnostics: Vec<ParseDiagnostic>,
+}
+
+/// Just enough of an entry to tell whether it can still be hit.
+#[derive(Deserialize)]
+struct CachedHeader {
+    version: u32,
+    path: String,
+}
+
+/// On-disk cache of the searchable text and metadata of session files,
+/// keyed by path and invalidated when a file's size or mtime changes.
+///
+/// Each session file gets its own JSON entry so that only changed files are
+/// rewritten.
+#[derive(Debug, Clone)]
+pub struct IndexCache {
+    dir: PathBuf,
+}
+
+impl IndexCache {
+    pub fn new(dir: PathBuf) -> Self {
+        Self { dir }
+    }
+
+    /// `$XDG_CACHE_HOME/claude-projects-viewer`, falling back to
+    /// `~/.cache/claude-projects-viewer`.
+    pub fn default_dir() -> Option<PathBuf> {
+        let base = match env::var_os("XDG_CACHE_HOME") {
+            Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+            _ => home_dir()?.join(".cache"),
+        };
+        Some(base.join("claude-projects-viewer"))
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Restores `entry` from the cache if it has not changed since it was
+    /// stored. The returned project carries texts but no messages.
+    pub fn get(&self, entry: &ProjectEntry) -> Option<ProjectFile> {
+        let data = fs::read(self.entry_path(&entry.path)).ok()?;
+        let cached: CachedFile = serde_json::from_slice(&data).ok()?;
+
+        let path = entry.path.to_string_lossy();
+        if cached.version != CACHE_VERSION
+            || cached.path != path
+            || cached.size != entry.size
+            || cached.modified != entry.modified.and_then(to_nanos)
+        {
+            return None;
+        }
+
+        let mut project = ProjectFile::new(cached.path);
+        project.project_name = cached.project_name;
+        project.resolve_path(Some(&cached.project_path));
+        project.size = cached.size;
+        project.modified = cached.modified.and_then(from_nanos);
+        project.texts = cached.texts;
+        project.resolve_title();
+        project.diagnostics = cached.diagnostics;
+        Some(project)
+    }
+
+    /// Writes the extracted text of a freshly parsed project.
+    pub fn put(&self, project: &ProjectFile) -> std::io::Result<()> {
+        let cached = CachedFile {
+            version: CACHE_VERSION,
+            path: project.path.clone(),
+            size: project.size,
+            modified: project.modified.and_then(to_nanos),
+            project_name: project.project_name.clone(),
+            project_path: project.project_path.clone(),
+            texts: project.texts.clone(),
+            diagnostics: project.diagnostics.clone(),
+        };
+
+        fs::create_dir_all(&self.dir)?;
+        let target = self.entry_path(Path::new(&project.path));
+        // Write then rename so a concurrent reader never sees half an entry
+        let partial = target.with_extension("json.tmp");
+        fs::write(&partial, serde_json::to_vec(&cached)?)?;
+        fs::rename(partial, target)
+    }
+
+    /// Removes entries that can no longer be hit: those written by another
+    /// cache version, stored under a name this build would not look up, or
+    /// for session files that have been deleted. Sweeps at most once per
+    /// [`PRUNE_INTERVAL`] and returns the number of entries removed.
+    pub fn prune(&self) -> std::io::Result<usize> {
+        let marker = self.dir.join(PRUNE_MARKER);
+        if let Ok(modified) = fs::metadata(&marker).and_then(|metadata| metadata.modified())
+            && modified.elapsed().is_ok_and(|elapsed| elapsed < PRUNE_INTERVAL)
+        {
+            return Ok(0);
+        }
+
+        let mut removed = 0;
+        for entry in fs::read_dir(&self.dir)? {
+            let path = entry?.path();
+            if path.extension().is_none_or(|extension| extension != "json") {
+                continue;
+            }
+            let live = fs::read(&path)
+                .ok()
+                .and_then(|data| serde_json::from_slice::<CachedHeader>(&data).ok())
+                .is_some_and(|header| {
+                    let source = Path::new(&header.path);
+                    header.version == CACHE_VERSION
+                        && self.entry_path(source).file_name() == path.file_name()
+                        && source.exists()
+                });
+            if !live && fs::remove_file(&path).is_ok() {
+                removed += 1;
+            }
+        }
+        fs::write(marker, [])?;
+        Ok(removed)
+    }
+
+    /// Entries are named after an FNV-1a hash of the session path, which,
+    /// unlike `DefaultHasher`, stays the same across Rust releases.
+    fn entry_path(&self, path: &Path) -> PathBuf {
+        let hash = path
+            .as_os_str()
+            .as_encoded_bytes()
+            .iter()
+            .fold(0xcbf2_9ce4_8422_2325_u64, |hash, &byte| (hash ^ byte as u64).wrapping_mul(0x0100_0000_01b3));
+        self.dir.join(format!("{:016x}.json", hash))
+    }
+}
+
+fn to_nanos(time: SystemTime) -> Option<u128> {
+    time.duration_since(UNIX_EPOCH).ok().map(|d| d.as_nanos())
+}
+
+fn from_nanos(nanos: u128) -> Option<SystemTime> {
+    let secs = u64::try_from(nanos / 1_000_000_000).ok()?;
+    UNIX_EPOCH.checked_add(Duration::new(secs, (nanos % 1_000_000_000) as u32))
+}
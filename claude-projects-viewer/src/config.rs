@@ -0,0 +1,168 @@
+use crate::cache::IndexCache;
+use crate::pricing::PricingTable;
+use home::home_dir;
+use std::env;
+use std::path::PathBuf;
+use std::thread;
+
+/// Number of session files loaded per page when no explicit window is given.
+pub const DEFAULT_LIMIT: usize = 30;
+
+/// Where session files are discovered and how many of them are loaded.
+#[derive(Debug, Clone)]
+pub struct LoadOptions {
+    /// Directories scanned for `**/*.jsonl` session files.
+    pub roots: Vec<PathBuf>,
+    /// Maximum number of files to load, newest first. `None` loads everything.
+    pub limit: Option<usize>,
+    /// Number of files parsed concurrently.
+    pub threads: usize,
+    /// Directory of the index cache. `None` disables the cache.
+    pub cache_dir: Option<PathBuf>,
+    /// Pricing table for cost estimates. `None` uses the default location.
+    pub pricing_file: Option<PathBuf>,
+}
+
+impl Default for LoadOptions {
+    fn default() -> Self {
+        Self {
+            roots: Self::default_roots(),
+            limit: Some(DEFAULT_LIMIT),
+            threads: Self::default_threads(),
+            cache_dir: IndexCache::default_dir(),
+            pricing_file: None,
+        }
+    }
+}
+
+impl LoadOptions {
+    /// The load-related flags, for usage messages.
+    pub const USAGE: &'static str =
+        "[--root DIR]... [--limit N | --all] [--threads N] [--cache-dir DIR | --no-cache] [--pricing FILE]";
+
+    /// Resolves the projects roots from the environment.
+    ///
+    /// `CLAUDE_CONFIG_DIR` may hold one or more config directories (separated
+    /// like `PATH`); each contributes its `projects` subdirectory. Without it
+    /// the default `~/.claude/projects` is used.
+    pub fn default_roots() -> Vec<PathBuf> {
+        if let Some(dirs) = env::var_os("CLAUDE_CONFIG_DIR") {
+            let roots: Vec<PathBuf> = env::split_paths(&dirs)
+                .filter(|dir| !dir.as_os_str().is_empty())
+                .map(|dir| dir.join("projects"))
+                .collect();
+            if !roots.is_empty() {
+                return roots;
+            }
+        }
+
+        home_dir()
+            .map(|home| vec![home.join(".claude").join("projects")])
+            .unwrap_or_default()
+    }
+
+    /// One parser thread per available core.
+    pub fn default_threads() -> usize {
+        thread::available_parallelism().map_or(1, |n| n.get())
+    }
+
+    /// Parses the load-related flags out of `args` (without the program name)
+    /// and returns the options together with the remaining arguments.
+    ///
+    /// Recognized flags:
+    /// - `--root <dir>`: scan `<dir>` for session files (repeatable, replaces the defaults)
+    /// - `--limit <n>`: load the `n` most recently modified files
+    /// - `--all`: load every file
+    /// - `--threads <n>`: parse `n` files concurrently (defaults to the number of cores)
+    /// - `--cache-dir <dir>`: keep the index cache in `<dir>`
+    /// - `--no-cache`: neither read nor write the index cache
+    /// - `--pricing <file>`: read model prices and budgets from `<file>`
+    pub fn from_args<I>(args: I) -> Result<(Self, Vec<String>), String>
+    where
+        I: IntoIterator<Item = String>,
+    {
+        let mut roots = Vec::new();
+        let mut limit = Some(DEFAULT_LIMIT);
+        let mut threads = Self::default_threads();
+        let mut cache_dir = IndexCache::default_dir();
+        let mut pricing_file = None;
+        let mut rest = Vec::new();
+
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--root" => {
+                    let dir = args.next().ok_or("--root requires a directory")?;
+                    roots.push(PathBuf::from(dir));
+                }
+                "--limit" => {
+                    let value = args.next().ok_or("--limit requires a number")?;
+                    let n = value
+                        .parse::<usize>()
+                        .map_err(|_| format!("invalid --limit value: {value}"))?;
+                    limit = Some(n);
+                }
+                "--all" => limit = None,
+                "--threads" => {
+                    let value = args.next().ok_or("--threads requires a number")?;
+                    threads = value
+                        .parse::<usize>()
+                        .ok()
+                        .filter(|&n| n > 0)
+                        .ok_or_else(|| format!("invalid --threads value: {value}"))?;
+                }
+                "--cache-dir" => {
+                    let dir = args.next().ok_or("--cache-dir requires a directory")?;
+                    cache_dir = Some(PathBuf::from(dir));
+                }
+                "--no-cache" => cache_dir = None,
+                "--pricing" => {
+                    let file = args.next().ok_or("--pricing requires a file")?;
+                    pricing_file = Some(PathBuf::from(file));
+                }
+                _ => rest.push(arg),
+            }
+        }
+
+        if roots.is_empty() {
+            roots = Self::default_roots();
+        }
+
+        Ok((
+            Self {
+                roots,
+                limit,
+                threads,
+                cache_dir,
+                pricing_file,
+            },
+            rest,
+        ))
+    }
+
+    /// Parses the process arguments, exiting with a message on invalid flags.
+    pub fn from_env_args() -> (Self, Vec<String>) {
+        match Self::from_args(env::args().skip(1)) {
+            Ok(parsed) => parsed,
+            Err(e) => {
+                eprintln!("{e}");
+                std::process::exit(2);
+            }
+        }
+    }
+
+    pub fn cache(&self) -> Option<IndexCache> {
+        self.cache_dir.clone().map(IndexCache::new)
+    }
+
+    /// The pricing table from `pricing_file`, the default location or the
+    /// built-in prices.
+    pub fn pricing(&self) -> Result<PricingTable, String> {
+        PricingTable::load(self.pricing_file.as_deref())
+    }
+
+    /// Page size used when the user asks for more files.
+    pub fn page_size(&self) -> usize {
+        self.limit.unwrap_or(DEFAULT_LIMIT).max(1)
+    }
+}
@@ -0,0 +1,243 @@
+use super::{metadata, role, tool_names};
+use crate::types::{Content, ImageSource, ProjectFile, SessionMessage, ToolResultContent, ToolResultItem, UserContent};
+use crate::ui::format_datetime;
+use std::collections::HashMap;
+
+const STYLE: &str = r#"
+:root { --bg: #f6f7f9; --fg: #1f2328; --muted: #6e7781; --user: #dbeafe; --assistant: #ffffff; --border: #d0d7de; --error: #cf222e; }
+@media (prefers-color-scheme: dark) {
+  :root { --bg: #0d1117; --fg: #e6edf3; --muted: #8d96a0; --user: #1f3a5f; --assistant: #161b22; --border: #30363d; --error: #f85149; }
+}
+* { box-sizing: border-box; }
+body { margin: 0; background: var(--bg); color: var(--fg); font: 15px/1.5 -apple-system, "Segoe UI", Helvetica, Arial, sans-serif; }
+header { position: sticky; top: 0; z-index: 1; display: flex; gap: 1em; align-items: center; padding: .75em 1.5em; background: var(--bg); border-bottom: 1px solid var(--border); }
+header h1 { margin: 0; font-size: 1.1em; }
+header input { flex: 1; max-width: 32em; padding: .4em .6em; font: inherit; color: inherit; background: var(--assistant); border: 1px solid var(--border); border-radius: 6px; }
+#count { color: var(--muted); font-size: .9em; }
+main { max-width: 960px; margin: 0 auto; padding: 1em 1.5em 4em; }
+.session { margin-bottom: 3em; }
+.session h2 { margin-bottom: .25em; }
+.meta { display: grid; grid-template-columns: max-content 1fr; gap: 0 1em; margin: 0 0 1.5em; color: var(--muted); font-size: .85em; }
+.meta dt { font-weight: 600; }
+.meta dd { margin: 0; font-family: ui-monospace, SFMono-Regular, Menlo, monospace; overflow-wrap: anywhere; }
+.message { display: flex; margin: .75em 0; }
+.bubble { max-width: 85%; padding: .6em .9em; border: 1px solid var(--border); border-radius: 12px; background: var(--assistant); overflow-wrap: anywhere; }
+.user { justify-content: flex-end; }
+.user .bubble { background: var(--user); border-bottom-right-radius: 4px; }
+.assistant .bubble { border-bottom-left-radius: 4px; }
+.tool-result .bubble, .system .bubble, .summary .bubble, .other .bubble { max-width: 100%; width: 100%; font-size: .9em; }
+.system .bubble, .summary .bubble { color: var(--muted); font-style: italic; }
+.sidechain { margin-left: 2em; }
+.head { display: flex; gap: .75em; color: var(--muted); font-size: .8em; margin-bottom: .25em; }
+.role { font-weight: 600; }
+.text { white-space: pre-wrap; }
+details { margin: .5em 0; border: 1px solid var(--border); border-radius: 6px; }
+details > summary { cursor: pointer; padding: .25em .6em; color: var(--muted); font-size: .85em; }
+details.error > summary { color: var(--error); }
+details > .text, details > pre { margin: 0; padding: .5em .75em; border-top: 1px solid var(--border); }
+pre { white-space: pre-wrap; font: .85em/1.45 ui-monospace, SFMono-Regular, Menlo, monospace; }
+img { max-width: 100%; border-radius: 6px; }
+.note { color: var(--muted); font-style: italic; }
+"#;
+
+const SCRIPT: &str = r#"
+const input = document.getElementById('search');
+const count = document.getElementById('count');
+input.addEventListener('input', () => {
+  const query = input.value.trim().toLowerCase();
+  let shown = 0;
+  for (const session of document.querySelectorAll('.session')) {
+    let any = false;
+    for (const message of session.querySelectorAll('.message')) {
+      const hit = !query || message.textContent.toLowerCase().includes(query);
+      message.hidden = !hit;
+      if (!hit) continue;
+      any = true;
+      shown++;
+      for (const details of message.querySelectorAll('details')) {
+        details.open = query !== '' && details.textContent.toLowerCase().includes(query);
+      }
+    }
+    session.hidden = !any;
+  }
+  count.textContent = query ? shown + ' matching messages' : '';
+});
+"#;
+
+/// Sessions as one self-contained HTML page: chat bubbles per message,
+/// tool calls and thinking folded into `<details>`, base64 images inlined,
+/// and a search box that filters messages in the browser. Nothing is loaded
+/// from outside the file.
+///
+/// Each session comes with the indices of the messages to include, or
+/// `None` for all of them. The projects must be loaded.
+pub fn sessions_html(sessions: &[(&ProjectFile, Option<&[usize]>)]) -> String {
+    let title = match sessions {
+        [(project, _)] => project.label().to_string(),
+        _ => format!("{} Claude sessions", sessions.len()),
+    };
+
+    let mut out = String::from("<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n");
+    out.push_str("<meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">\n");
+    out.push_str(&format!("<title>{}</title>\n<style>{}</style>\n</head>\n<body>\n", escape(&title), STYLE));
+    out.push_str(&format!(
+        "<header><h1>{}</h1><input id=\"search\" type=\"search\" placeholder=\"Search messages\" autofocus><span id=\"count\"></span></header>\n<main>\n",
+        escape(&title),
+    ));
+    for (project, indices) in sessions {
+        write_session(&mut out, project, *indices);
+    }
+    out.push_str(&format!("</main>\n<script>{}</script>\n</body>\n</html>\n", SCRIPT));
+    out
+}
+
+fn write_session(out: &mut String, project: &ProjectFile, indices: Option<&[usize]>) {
+    let messages: Vec<&SessionMessage> = match indices {
+        Some(indices) => indices.iter().filter_map(|&i| project.messages.get(i)).collect(),
+        None => project.messages.iter().collect(),
+    };
+    let tool_names = tool_names(project);
+
+    out.push_str(&format!("<section class=\"session\">\n<h2>{}</h2>\n<dl class=\"meta\">", escape(project.label())));
+    for (key, value) in metadata(project) {
+        out.push_str(&format!("<dt>{}</dt><dd>{}</dd>", key, escape(value)));
+    }
+    out.push_str("</dl>\n");
+    for message in messages {
+        write_message(out, message, &tool_names);
+    }
+    out.push_str("</section>\n");
+}
+
+fn write_message(out: &mut String, message: &SessionMessage, tool_names: &HashMap<&str, &str>) {
+    let role = role(message);
+    let class = match role.as_str() {
+        "User" | "User (meta)" => "user",
+        "Assistant" => "assistant",
+        "Tool result" => "tool-result",
+        "System" => "system",
+        "Summary" => "summary",
+        _ => "other",
+    };
+    let sidechain = if message.is_sidechain() { " sidechain" } else { "" };
+    out.push_str(&format!(
+        "<article class=\"message {}{}\"><div class=\"bubble\">\n<div class=\"head\"><span class=\"role\">{}</span>",
+        class,
+        sidechain,
+        escape(&role),
+    ));
+    if let Some(time) = message.get_time() {
+        out.push_str(&format!("<time datetime=\"{}\">{}</time>", time.to_rfc3339(), format_datetime(Some(time))));
+    }
+    if message.is_sidechain() {
+        out.push_str("<span>sidechain</span>");
+    }
+    out.push_str("</div>\n");
+
+    match message {
+        SessionMessage::Summary { summary, .. } => text(out, summary),
+        SessionMessage::System { content, .. } => text(out, content),
+        SessionMessage::User { message, .. } => match &message.content {
+            UserContent::String(content) => text(out, content),
+            UserContent::Array(contents) => {
+                for content in contents {
+                    write_content(out, content, tool_names);
+                }
+            }
+        },
+        SessionMessage::Assistant { message, .. } => {
+            for content in message.content() {
+                write_content(out, content, tool_names);
+            }
+        }
+        SessionMessage::Unknown { kind, raw } => folded(out, &format!("Record: {}", kind), "raw", &pretty_json(raw)),
+    }
+    out.push_str("</div></article>\n");
+}
+
+fn write_content(out: &mut String, content: &Content, tool_names: &HashMap<&str, &str>) {
+    match content {
+        Content::Text { text: content, .. } => text(out, content),
+        Content::ToolUse { name, input, .. } => {
+            folded(out, &format!("Tool call: {}", name), "tool", &serde_json::to_string_pretty(input).unwrap_or_default());
+        }
+        Content::ToolResult { tool_use_id, content, is_error, .. } => {
+            let tool = tool_names.get(tool_use_id.as_str()).copied().unwrap_or("unknown tool");
+            let (label, class) = if *is_error == Some(true) { ("Error", "tool error") } else { ("Result", "tool") };
+            let summary = format!("{}: {}", label, tool);
+            match content {
+                Some(ToolResultContent::String(output)) => folded(out, &summary, class, output),
+                Some(ToolResultContent::Array(items)) => {
+                    out.push_str(&format!("<details class=\"{}\"><summary>{}</summary>", class, escape(&summary)));
+                    for item in items {
+                        match item {
+                            ToolResultItem::Text { text, .. } => {
+                                out.push_str(&format!("<pre>{}</pre>", escape(text.trim_end_matches('\n'))))
+                            }
+                            ToolResultItem::Image { source, .. } => write_image(out, source),
+                            ToolResultItem::Unknown { raw, .. } => out.push_str(&format!("<pre>{}</pre>", escape(&pretty_json(raw)))),
+                        }
+                    }
+                    out.push_str("</details>\n");
+                }
+                None => folded(out, &summary, class, "(no output)"),
+            }
+        }
+        Content::Thinking { thinking, .. } => {
+            out.push_str("<details class=\"thinking\"><summary>Thinking</summary>");
+            text(out, thinking);
+            out.push_str("</details>\n");
+        }
+        Content::Image { source, .. } => write_image(out, source),
+        Content::Unknown { kind, raw } => folded(out, &format!("Content: {}", kind), "raw", &pretty_json(raw)),
+    }
+}
+
+fn text(out: &mut String, content: &str) {
+    out.push_str(&format!("<div class=\"text\">{}</div>\n", escape(content.trim_end())));
+}
+
+/// A collapsed block with `summary` as its label and `body` preformatted.
+fn folded(out: &mut String, summary: &str, class: &str, body: &str) {
+    out.push_str(&format!(
+        "<details class=\"{}\"><summary>{}</summary><pre>{}</pre></details>\n",
+        class,
+        escape(summary),
+        escape(body.trim_end_matches('\n')),
+    ));
+}
+
+/// Inlines base64 image data as a `data:` URL. Anything else (URLs, files,
+/// unexpected media types) is only noted, since it would need an external
+/// request.
+fn write_image(out: &mut String, source: &ImageSource) {
+    let media_type = source.media_type.as_deref().unwrap_or("unknown type");
+    let inline = source.source_type == "base64"
+        && matches!(media_type, "image/png" | "image/jpeg" | "image/gif" | "image/webp")
+        && source.data.as_deref().is_some_and(|data| {
+            data.bytes().all(|byte| byte.is_ascii_alphanumeric() || matches!(byte, b'+' | b'/' | b'='))
+        });
+    match &source.data {
+        Some(data) if inline => out.push_str(&format!("<img alt=\"image\" src=\"data:{};base64,{}\">\n", media_type, data)),
+        _ => out.push_str(&format!("<p class=\"note\">[image: {}]</p>\n", escape(media_type))),
+    }
+}
+
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+fn pretty_json(value: &serde_json::Value) -> String {
+    serde_json::to_string_pretty(value).unwrap_or_default()
+}
@@ -0,0 +1,22 @@
+use crate::types::ProjectFile;
+
+/// The messages at `indices` (every message when `None`) as session JSONL,
+/// one record per line in file order. Fields the viewer does not model are
+/// written back as they were read, so the output loads like any session
+/// file. The project must be loaded.
+pub fn session_jsonl(project: &ProjectFile, indices: Option<&[usize]>) -> Result<String, String> {
+    let mut indices: Vec<usize> = match indices {
+        Some(indices) => indices.to_vec(),
+        None => (0..project.messages.len()).collect(),
+    };
+    indices.sort_unstable();
+    indices.dedup();
+
+    let mut out = String::new();
+    for message in indices.iter().filter_map(|&i| project.messages.get(i)) {
+        let line = serde_json::to_string(message).map_err(|e| format!("cannot serialize {} record: {e}", message.get_type()))?;
+        out.push_str(&line);
+        out.push('\n');
+    }
+    Ok(out)
+}
@@ -0,0 +1,122 @@
+use super::{metadata, role, tool_names};
+use crate::types::{Content, ProjectFile, SessionMessage, ToolResultContent, ToolResultItem, UserContent};
+use crate::ui::format_datetime;
+use std::collections::HashMap;
+
+/// A session as Markdown: a front-matter header with the session id, cwd,
+/// branch and model, then one heading per message. Tool inputs and results
+/// are fenced and thinking is folded into a `<details>` block.
+///
+/// `indices` picks the messages to include (indices into `project.messages`);
+/// `None` includes every message. The project must be loaded.
+pub fn session_markdown(project: &ProjectFile, indices: Option<&[usize]>) -> String {
+    let messages: Vec<&SessionMessage> = match indices {
+        Some(indices) => indices.iter().filter_map(|&i| project.messages.get(i)).collect(),
+        None => project.messages.iter().collect(),
+    };
+    let tool_names = tool_names(project);
+
+    let mut out = String::from("---\n");
+    for (key, value) in metadata(project) {
+        // JSON strings are valid YAML scalars and need no further escaping
+        out.push_str(&format!("{}: {}\n", key, serde_json::Value::from(value)));
+    }
+    out.push_str("---\n\n");
+    out.push_str(&format!("# {}\n\n", project.label()));
+
+    for message in messages {
+        write_message(&mut out, message, &tool_names);
+    }
+    out
+}
+
+fn write_message(out: &mut String, message: &SessionMessage, tool_names: &HashMap<&str, &str>) {
+    let mut heading = format!("## {}", role(message));
+    if let Some(time) = message.get_time() {
+        heading.push_str(&format!(" · {}", format_datetime(Some(time))));
+    }
+    if message.is_sidechain() {
+        heading.push_str(" · sidechain");
+    }
+    out.push_str(&heading);
+    out.push_str("\n\n");
+
+    match message {
+        SessionMessage::Summary { summary, .. } => paragraph(out, summary),
+        SessionMessage::System { content, .. } => {
+            for line in content.lines() {
+                out.push_str(&format!("> {}\n", line));
+            }
+            out.push('\n');
+        }
+        SessionMessage::User { message, .. } => match &message.content {
+            UserContent::String(text) => paragraph(out, text),
+            UserContent::Array(contents) => {
+                for content in contents {
+                    write_content(out, content, tool_names);
+                }
+            }
+        },
+        SessionMessage::Assistant { message, .. } => {
+            for content in message.content() {
+                write_content(out, content, tool_names);
+            }
+        }
+        SessionMessage::Unknown { raw, .. } => fenced(out, "json", &pretty_json(raw)),
+    }
+}
+
+fn write_content(out: &mut String, content: &Content, tool_names: &HashMap<&str, &str>) {
+    match content {
+        Content::Text { text, .. } => paragraph(out, text),
+        Content::ToolUse { name, input, .. } => {
+            out.push_str(&format!("**Tool call: {}**\n\n", name));
+            fenced(out, "json", &serde_json::to_string_pretty(input).unwrap_or_default());
+        }
+        Content::ToolResult { tool_use_id, content, is_error, .. } => {
+            let tool = tool_names.get(tool_use_id.as_str()).copied().unwrap_or("unknown tool");
+            let label = if *is_error == Some(true) { "Error" } else { "Result" };
+            out.push_str(&format!("**{}: {}**\n\n", label, tool));
+            match content {
+                Some(ToolResultContent::String(text)) => fenced(out, "", text),
+                Some(ToolResultContent::Array(items)) => {
+                    for item in items {
+                        match item {
+                            ToolResultItem::Text { text, .. } => fenced(out, "", text),
+                            ToolResultItem::Image { source, .. } => image_note(out, source.media_type.as_deref()),
+                            ToolResultItem::Unknown { raw, .. } => fenced(out, "json", &pretty_json(raw)),
+                        }
+                    }
+                }
+                None => paragraph(out, "*(no output)*"),
+            }
+        }
+        Content::Thinking { thinking, .. } => {
+            out.push_str("<details>\n<summary>Thinking</summary>\n\n");
+            paragraph(out, thinking);
+            out.push_str("</details>\n\n");
+        }
+        Content::Image { source, .. } => image_note(out, source.media_type.as_deref()),
+        Content::Unknown { raw, .. } => fenced(out, "json", &pretty_json(raw)),
+    }
+}
+
+fn paragraph(out: &mut String, text: &str) {
+    out.push_str(text.trim_end());
+    out.push_str("\n\n");
+}
+
+fn image_note(out: &mut String, media_type: Option<&str>) {
+    paragraph(out, &format!("*[image: {}]*", media_type.unwrap_or("unknown type")));
+}
+
+/// A code block whose fence is longer than any run of backticks in `text`.
+fn fenced(out: &mut String, lang: &str, text: &str) {
+    let longest = text.split(|c| c != '`').map(str::len).max().unwrap_or(0);
+    let fence = "`".repeat(longest.max(2) + 1);
+    out.push_str(&format!("{}{}\n{}\n{}\n\n", fence, lang, text.trim_end_matches('\n'), fence));
+}
+
+fn pretty_json(value: &serde_json::Value) -> String {
+    serde_json::to_string_pretty(value).unwrap_or_default()
+}
@@ -0,0 +1,189 @@
+//! Sessions written out as documents that can be read or shared without
+//! the viewer.
+
+mod html;
+mod jsonl;
+mod markdown;
+
+pub use html::sessions_html;
+pub use jsonl::session_jsonl;
+pub use markdown::session_markdown;
+
+use crate::parser::ProjectParser;
+use crate::types::{Content, ProjectFile, SearchResult, SessionMessage, UserContent};
+use std::collections::HashMap;
+use std::fs::{self, OpenOptions};
+use std::io::{ErrorKind, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ExportFormat {
+    #[default]
+    Markdown,
+    /// One self-contained page for every exported session.
+    Html,
+    /// The selected records, re-serialized as a session file.
+    Jsonl,
+}
+
+/// An exported document and the name of the file it is written to.
+#[derive(Debug, Clone)]
+pub struct Document {
+    pub file_name: String,
+    pub content: String,
+}
+
+impl ExportFormat {
+    /// Parses a `--format` value.
+    pub fn parse(name: &str) -> Result<Self, String> {
+        match name {
+            "markdown" | "md" => Ok(ExportFormat::Markdown),
+            "html" => Ok(ExportFormat::Html),
+            "jsonl" => Ok(ExportFormat::Jsonl),
+            _ => Err(format!("unknown export format `{name}` (expected markdown, html or jsonl)")),
+        }
+    }
+
+    /// File extension of an exported session, without the dot. Trimmed
+    /// session files get their own, so they are never mistaken for (or
+    /// written over) the logs they came from.
+    pub fn extension(self) -> &'static str {
+        match self {
+            ExportFormat::Markdown => "md",
+            ExportFormat::Html => "html",
+            ExportFormat::Jsonl => "export.jsonl",
+        }
+    }
+}
+
+/// The messages of `results` in `format`: a document per session, or a
+/// single one for formats that hold several sessions.
+pub fn export_results(results: &[SearchResult], format: ExportFormat) -> Result<Vec<Document>, String> {
+    let projects = results
+        .iter()
+        .map(|result| with_records(&result.project))
+        .collect::<Result<Vec<_>, _>>()?;
+    let document = |stem: &str, content| Document {
+        file_name: format!("{}.{}", stem, format.extension()),
+        content,
+    };
+    Ok(match format {
+        ExportFormat::Markdown => results
+            .iter()
+            .zip(&projects)
+            .map(|(result, project)| document(project.session_id(), session_markdown(project, Some(&result.message_indices))))
+            .collect(),
+        ExportFormat::Html => {
+            let sessions: Vec<_> = results
+                .iter()
+                .zip(&projects)
+                .map(|(result, project)| (project.as_ref(), Some(result.message_indices.as_slice())))
+                .collect();
+            let stem = match projects.as_slice() {
+                [project] => project.session_id(),
+                _ => "sessions",
+            };
+            vec![document(stem, sessions_html(&sessions))]
+        }
+        ExportFormat::Jsonl => results
+            .iter()
+            .zip(&projects)
+            .map(|(result, project)| {
+                Ok(document(project.session_id(), session_jsonl(project, Some(&result.message_indices))?))
+            })
+            .collect::<Result<_, String>>()?,
+    })
+}
+
+/// Writes `documents` into `dir`, creating it if needed, and returns the
+/// paths written. Existing files are never replaced.
+pub fn write_documents(documents: &[Document], dir: &Path) -> Result<Vec<PathBuf>, String> {
+    fs::create_dir_all(dir).map_err(|e| format!("cannot create {}: {e}", dir.display()))?;
+    documents
+        .iter()
+        .map(|document| {
+            let path = dir.join(&document.file_name);
+            let written = OpenOptions::new()
+                .write(true)
+                .create_new(true)
+                .open(&path)
+                .and_then(|mut file| file.write_all(document.content.as_bytes()));
+            match written {
+                Ok(()) => Ok(path),
+                Err(e) if e.kind() == ErrorKind::AlreadyExists => {
+                    Err(format!("{} already exists, not overwriting it", path.display()))
+                }
+                Err(e) => Err(format!("cannot write {}: {e}", path.display())),
+            }
+        })
+        .collect()
+}
+
+/// `project` with its full records. Files restored from the index cache
+/// only carry their texts, so the session file is read again.
+pub fn with_records(project: &Arc<ProjectFile>) -> Result<Arc<ProjectFile>, String> {
+    if project.is_loaded() {
+        return Ok(project.clone());
+    }
+    let mut loaded = ProjectParser::load_project_file(Path::new(&project.path))
+        .map_err(|e| format!("cannot read {}: {e}", project.path))?;
+    // 追記されていても検索結果のインデックスがずれないよう、キャッシュ時点の件数に揃える
+    loaded.messages.truncate(project.texts.len());
+    loaded.texts.truncate(project.texts.len());
+    // The title may come from a summary in another session's file
+    loaded.title = project.title.clone();
+    Ok(Arc::new(loaded))
+}
+
+/// Session id, title, cwd, branch and model of a loaded session, leaving
+/// out what it does not record.
+fn metadata(project: &ProjectFile) -> Vec<(&'static str, &str)> {
+    let cwd = project.messages.iter().find_map(SessionMessage::get_cwd).unwrap_or(&project.project_path);
+    [
+        ("session_id", Some(project.session_id())),
+        ("title", project.title.as_deref()),
+        ("cwd", Some(cwd)),
+        ("branch", project.texts.iter().find_map(|text| text.git_branch.as_deref())),
+        ("model", project.texts.iter().find_map(|text| text.model.as_deref())),
+    ]
+    .into_iter()
+    .filter_map(|(key, value)| Some((key, value.filter(|value| !value.is_empty())?)))
+    .collect()
+}
+
+/// Name of the tool each call id belongs to, so results can be labelled.
+fn tool_names(project: &ProjectFile) -> HashMap<&str, &str> {
+    project
+        .messages
+        .iter()
+        .flat_map(|message| match message {
+            SessionMessage::Assistant { message, .. } => message.content(),
+            _ => &[],
+        })
+        .filter_map(|content| match content {
+            Content::ToolUse { id, name, .. } => Some((id.as_str(), name.as_str())),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Who a message is from. User records carrying only tool results are
+/// labelled as such rather than as something the user typed.
+fn role(message: &SessionMessage) -> String {
+    match message {
+        SessionMessage::Summary { .. } => "Summary".to_string(),
+        SessionMessage::System { .. } => "System".to_string(),
+        SessionMessage::User { message, is_meta, .. } => {
+            let results_only = matches!(&message.content, UserContent::Array(contents)
+                if !contents.is_empty() && contents.iter().all(|content| matches!(content, Content::ToolResult { .. })));
+            match (results_only, *is_meta == Some(true)) {
+                (true, _) => "Tool result".to_string(),
+                (false, true) => "User (meta)".to_string(),
+                (false, false) => "User".to_string(),
+            }
+        }
+        SessionMessage::Assistant { .. } => "Assistant".to_string(),
+        SessionMessage::Unknown { kind, .. } => kind.clone(),
+    }
+}
@@ -0,0 +1,69 @@
+use crate::types::{SearchResult, TokenUsage};
+use std::collections::HashMap;
+use std::time::SystemTime;
+
+/// A project directory and the session files in it that matched a search.
+#[derive(Debug, Clone)]
+pub struct ProjectGroup {
+    /// Short name of the project, see [`ProjectFile::short_name`](crate::types::ProjectFile::short_name).
+    pub name: String,
+    pub path: String,
+    /// Indices into the search results, best (or newest) session first.
+    pub sessions: Vec<usize>,
+}
+
+impl ProjectGroup {
+    /// Groups `results` by project path, keeping the order in which each
+    /// project first appears, so the project of the best result comes first.
+    pub fn group(results: &[SearchResult]) -> Vec<ProjectGroup> {
+        let mut groups: Vec<ProjectGroup> = Vec::new();
+        let mut by_path: HashMap<&str, usize> = HashMap::new();
+        for (i, result) in results.iter().enumerate() {
+            let project = &result.project;
+            match by_path.get(project.project_path.as_str()) {
+                Some(&at) => groups[at].sessions.push(i),
+                None => {
+                    by_path.insert(&project.project_path, groups.len());
+                    groups.push(ProjectGroup {
+                        name: project.short_name.clone(),
+                        path: project.project_path.clone(),
+                        sessions: vec![i],
+                    });
+                }
+            }
+        }
+        groups
+    }
+
+    pub fn results<'a>(&self, results: &'a [SearchResult]) -> impl Iterator<Item = &'a SearchResult> {
+        self.sessions.iter().filter_map(|&i| results.get(i))
+    }
+
+    /// Matching messages across every session.
+    pub fn message_count(&self, results: &[SearchResult]) -> usize {
+        self.results(results).map(SearchResult::len).sum()
+    }
+
+    /// Score of the best matching session, if the results were ranked.
+    pub fn score(&self, results: &[SearchResult]) -> Option<f32> {
+        self.results(results).filter_map(SearchResult::score).reduce(f32::max)
+    }
+
+    /// When the most recently written session was last modified.
+    pub fn last_activity(&self, results: &[SearchResult]) -> Option<SystemTime> {
+        self.results(results).filter_map(|result| result.project.modified).max()
+    }
+
+    /// Tokens used by every session, matching or not.
+    pub fn tokens(&self, results: &[SearchResult]) -> TokenUsage {
+        let mut tokens = TokenUsage::default();
+        for result in self.results(results) {
+            tokens += result.project.tokens();
+        }
+        tokens
+    }
+
+    pub fn diagnostic_count(&self, results: &[SearchResult]) -> usize {
+        self.results(results).map(|result| result.project.diagnostics.len()).sum()
+    }
+}
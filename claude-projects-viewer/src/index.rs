@@ -0,0 +1,367 @@
+use crate::query::Query;
+use crate::types::{ProjectFile, Scope, Scopes, SearchResult};
+use std::collections::{BTreeMap, HashMap};
+use std::ops::Range;
+use std::sync::Arc;
+
+// BM25 parameters, the usual defaults.
+const K1: f64 = 1.2;
+const B: f64 = 0.75;
+
+/// Occurrences of a term in one doc.
+#[derive(Debug, Clone)]
+struct Posting {
+    doc: u32,
+    /// Token positions within the doc, ascending.
+    positions: Vec<u32>,
+}
+
+/// The text of one message in one scope, the unit the index ranks.
+#[derive(Debug, Clone, Copy)]
+struct Doc {
+    /// Index into `SearchIndex::messages`.
+    message: u32,
+    scope: Scope,
+    /// Number of tokens in the doc.
+    len: u32,
+}
+
+/// Terms of one project's messages, computed off the UI thread and then
+/// merged with [`SearchIndex::insert`].
+pub struct AnalyzedProject {
+    pub project: Arc<ProjectFile>,
+    /// The docs of each message.
+    messages: Vec<Vec<AnalyzedDoc>>,
+}
+
+struct AnalyzedDoc {
+    scope: Scope,
+    /// Number of tokens, counting gaps between content blocks.
+    len: u32,
+    /// Positions of each term.
+    terms: HashMap<String, Vec<u32>>,
+}
+
+/// Inverted index over the text of every loaded message, ranked with BM25.
+///
+/// Query words match indexed terms by prefix, so typing part of a word
+/// already finds it. Phrases are matched using term positions. Each scope of
+/// a message is indexed separately so searches can be limited to some.
+#[derive(Debug, Clone, Default)]
+pub struct SearchIndex {
+    projects: Vec<Arc<ProjectFile>>,
+    /// (project, message) of every indexed message.
+    messages: Vec<(u32, u32)>,
+    docs: Vec<Doc>,
+    postings: BTreeMap<String, Vec<Posting>>,
+    total_len: u64,
+}
+
+impl SearchIndex {
+    pub fn build(projects: &[Arc<ProjectFile>]) -> Self {
+        let mut index = Self::default();
+        for project in projects {
+            index.insert(Self::analyze(Arc::clone(project)));
+        }
+        index
+    }
+
+    /// Tokenizes every message of `project`.
+    pub fn analyze(project: Arc<ProjectFile>) -> AnalyzedProject {
+        let messages = project
+            .texts
+            .iter()
+            .map(|text| {
+                let mut docs: Vec<AnalyzedDoc> = Vec::new();
+                for (scope, content) in text.sections() {
+                    let at = match docs.iter().position(|doc| doc.scope == scope) {
+                        Some(at) => at,
+                        None => {
+                            docs.push(AnalyzedDoc {
+                                scope,
+                                len: 0,
+                                terms: HashMap::new(),
+                            });
+                            docs.len() - 1
+                        }
+                    };
+                    let doc = &mut docs[at];
+                    for token in tokenize(content) {
+                        doc.terms.entry(token).or_default().push(doc.len);
+                        doc.len += 1;
+                    }
+                    // Keep phrases from spanning two content blocks
+                    doc.len += 1;
+                }
+                docs
+            })
+            .collect();
+        AnalyzedProject { project, messages }
+    }
+
+    pub fn insert(&mut self, analyzed: AnalyzedProject) {
+        let project = self.projects.len() as u32;
+        self.projects.push(analyzed.project);
+
+        for (message, docs) in analyzed.messages.into_iter().enumerate() {
+            let id = self.messages.len() as u32;
+            self.messages.push((project, message as u32));
+            for analyzed in docs {
+                let doc = self.docs.len() as u32;
+                self.docs.push(Doc {
+                    message: id,
+                    scope: analyzed.scope,
+                    len: analyzed.len,
+                });
+                self.total_len += analyzed.len as u64;
+                for (term, positions) in analyzed.terms {
+                    self.postings.entry(term).or_default().push(Posting { doc, positions });
+                }
+            }
+        }
+    }
+
+    /// Swaps in a new version of an indexed project, found by path. Its
+    /// texts must be unchanged, e.g. when only the title was resolved again.
+    pub fn replace(&mut self, project: Arc<ProjectFile>) {
+        if let Some(indexed) = self.projects.iter_mut().find(|indexed| indexed.path == project.path) {
+            *indexed = project;
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.projects.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.projects.is_empty()
+    }
+
+    /// Returns the projects with messages matching `query` within `scopes`,
+    /// best match first. When the query searches for text, each result
+    /// carries the BM25 score of its messages and the scopes they matched in;
+    /// pure filters keep the newest project first.
+    pub fn search(&self, query: &Query, scopes: Scopes) -> Vec<SearchResult> {
+        let matched = self.evaluate(query, scopes);
+
+        let mut terms: Vec<String> = query.positive_text().into_iter().flat_map(tokenize).collect();
+        terms.sort();
+        terms.dedup();
+        let ranked = !terms.is_empty();
+
+        // Sum the scores of each message's docs and note where the terms were found
+        let mut scores: HashMap<u32, (f64, Scopes)> = HashMap::new();
+        for (doc, score) in self.score(&terms) {
+            let doc = self.docs[doc as usize];
+            if scopes.contains(doc.scope) {
+                let entry = scores.entry(doc.message).or_insert((0.0, Scopes::NONE));
+                entry.0 += score;
+                entry.1.insert(doc.scope);
+            }
+        }
+
+        // Group matching messages by project
+        let mut by_project: HashMap<u32, Vec<(usize, f32, Scopes)>> = HashMap::new();
+        for (message, _) in matched.iter().enumerate().filter(|(_, matched)| **matched) {
+            let (score, found_in) = scores.get(&(message as u32)).copied().unwrap_or((0.0, Scopes::NONE));
+            let (project, message) = self.messages[message];
+            by_project
+                .entry(project)
+                .or_default()
+                .push((message as usize, score as f32, found_in));
+        }
+
+        let mut results: Vec<SearchResult> = by_project
+            .into_iter()
+            .map(|(project, hits)| SearchResult {
+                project: Arc::clone(&self.projects[project as usize]),
+                message_indices: hits.iter().map(|&(message, _, _)| message).collect(),
+                scores: if ranked { hits.iter().map(|&(_, score, _)| score).collect() } else { Vec::new() },
+                scopes: if ranked { hits.iter().map(|&(_, _, scopes)| scopes).collect() } else { Vec::new() },
+            })
+            .collect();
+
+        results.sort_by(|a, b| {
+            b.score()
+                .partial_cmp(&a.score())
+                .unwrap_or(std::cmp::Ordering::Equal)
+                .then_with(|| b.project.modified.cmp(&a.project.modified))
+        });
+        results
+    }
+
+    /// Which messages match `query` within `scopes`, indexed like `messages`.
+    fn evaluate(&self, query: &Query, scopes: Scopes) -> Vec<bool> {
+        let all = || vec![true; self.messages.len()];
+        match query {
+            Query::Term(word) => {
+                let tokens = tokenize(word);
+                match tokens.as_slice() {
+                    [] => all(),
+                    // A single word matches by prefix; `foo-bar` is a phrase
+                    [token] => {
+                        let mut matched = vec![false; self.messages.len()];
+                        for doc in self.term_frequencies(token).into_keys() {
+                            let doc = self.docs[doc as usize];
+                            if scopes.contains(doc.scope) {
+                                matched[doc.message as usize] = true;
+                            }
+                        }
+                        matched
+                    }
+                    _ => self.phrase_matches(&tokens, scopes),
+                }
+            }
+            Query::Phrase(text) => {
+                let tokens = tokenize(text);
+                if tokens.is_empty() {
+                    all()
+                } else {
+                    self.phrase_matches(&tokens, scopes)
+                }
+            }
+            Query::Filter(filter) => self
+                .messages
+                .iter()
+                .map(|&(project, message)| {
+                    let project = &self.projects[project as usize];
+                    filter.matches(project, &project.texts[message as usize])
+                })
+                .collect(),
+            Query::Not(inner) => self
+                .evaluate(inner, scopes)
+                .into_iter()
+                .map(|matched| !matched)
+                .collect(),
+            Query::And(parts) => parts.iter().fold(all(), |acc, part| {
+                acc.into_iter()
+                    .zip(self.evaluate(part, scopes))
+                    .map(|(a, b)| a && b)
+                    .collect()
+            }),
+            Query::Or(parts) => parts.iter().fold(vec![false; self.messages.len()], |acc, part| {
+                acc.into_iter()
+                    .zip(self.evaluate(part, scopes))
+                    .map(|(a, b)| a || b)
+                    .collect()
+            }),
+        }
+    }
+
+    /// Messages containing `tokens` as consecutive, exact terms within one
+    /// of `scopes`.
+    fn phrase_matches(&self, tokens: &[String], scopes: Scopes) -> Vec<bool> {
+        let mut matched = vec![false; self.messages.len()];
+        let Some(first) = self.postings.get(&tokens[0]) else {
+            return matched;
+        };
+        for posting in first {
+            let doc = self.docs[posting.doc as usize];
+            if !scopes.contains(doc.scope) {
+                continue;
+            }
+            let found = posting.positions.iter().any(|&start| {
+                tokens[1..].iter().zip(1..).all(|(token, offset)| {
+                    self.positions(token, posting.doc)
+                        .is_some_and(|positions| positions.binary_search(&(start + offset)).is_ok())
+                })
+            });
+            if found {
+                matched[doc.message as usize] = true;
+            }
+        }
+        matched
+    }
+
+    /// Positions of the exact `term` in `doc`.
+    fn positions(&self, term: &str, doc: u32) -> Option<&[u32]> {
+        // Postings are appended in doc order
+        let postings = self.postings.get(term)?;
+        let at = postings.binary_search_by_key(&doc, |posting| posting.doc).ok()?;
+        Some(&postings[at].positions)
+    }
+
+    /// BM25 score of every doc containing at least one of `terms`.
+    fn score(&self, terms: &[String]) -> HashMap<u32, f64> {
+        let mut scores = HashMap::new();
+        if self.docs.is_empty() {
+            return scores;
+        }
+        let doc_count = self.docs.len() as f64;
+        let avg_len = (self.total_len as f64 / doc_count).max(1.0);
+
+        for term in terms {
+            let frequencies = self.term_frequencies(term);
+            let df = frequencies.len() as f64;
+            let idf = ((doc_count - df + 0.5) / (df + 0.5) + 1.0).ln();
+
+            for (doc, tf) in frequencies {
+                let tf = tf as f64;
+                let len = self.docs[doc as usize].len as f64;
+                *scores.entry(doc).or_default() +=
+                    idf * tf * (K1 + 1.0) / (tf + K1 * (1.0 - B + B * len / avg_len));
+            }
+        }
+        scores
+    }
+
+    /// Term frequency per doc for every indexed term starting with `prefix`.
+    fn term_frequencies(&self, prefix: &str) -> HashMap<u32, u32> {
+        let mut frequencies = HashMap::new();
+        for (_, postings) in self
+            .postings
+            .range(prefix.to_string()..)
+            .take_while(|(term, _)| term.starts_with(prefix))
+        {
+            for posting in postings {
+                *frequencies.entry(posting.doc).or_default() += posting.positions.len() as u32;
+            }
+        }
+        frequencies
+    }
+}
+
+/// Splits text into lowercase terms.
+///
+/// Runs of letters, digits and `_` form one term. Han, kana and hangul have
+/// no spaces between words, so each of those characters is its own term.
+pub fn tokenize(text: &str) -> Vec<String> {
+    token_spans(text).into_iter().map(|(_, token)| token).collect()
+}
+
+/// Like [`tokenize`], also returning the byte range of each term in `text`.
+pub fn token_spans(text: &str) -> Vec<(Range<usize>, String)> {
+    let mut tokens = Vec::new();
+    let mut current = String::new();
+    let mut start = 0;
+
+    for (i, c) in text.char_indices() {
+        if is_cjk(c) {
+            if !current.is_empty() {
+                tokens.push((start..i, std::mem::take(&mut current)));
+            }
+            tokens.push((i..i + c.len_utf8(), c.to_string()));
+        } else if c.is_alphanumeric() || c == '_' {
+            if current.is_empty() {
+                start = i;
+            }
+            current.extend(c.to_lowercase());
+        } else if !current.is_empty() {
+            tokens.push((start..i, std::mem::take(&mut current)));
+        }
+    }
+    if !current.is_empty() {
+        tokens.push((start..text.len(), current));
+    }
+    tokens
+}
+
+fn is_cjk(c: char) -> bool {
+    matches!(c,
+        '\u{3040}'..='\u{30ff}'   // hiragana, katakana
+        | '\u{3400}'..='\u{4dbf}' // CJK extension A
+        | '\u{4e00}'..='\u{9fff}' // CJK unified ideographs
+        | '\u{ac00}'..='\u{d7af}' // hangul syllables
+        | '\u{f900}'..='\u{faff}' // CJK compatibility ideographs
+    )
+}
@@ -1,2 +1,17 @@
+pub mod cache;
+pub mod config;
+pub mod export;
+pub mod group;
+pub mod index;
+pub mod loader;
+pub mod matcher;
 pub mod parser;
-pub mod types;
\ No newline at end of file
+pub mod paths;
+pub mod pricing;
+pub mod query;
+pub mod snippet;
+pub mod stats;
+pub mod thread;
+pub mod title;
+pub mod types;
+pub mod ui;
\ No newline at end of file
@@ -0,0 +1,238 @@
+use crate::cache::IndexCache;
+use crate::config::LoadOptions;
+use crate::index::{AnalyzedProject, SearchIndex};
+use crate::matcher::{Matcher, SearchMode};
+use crate::parser::{ProjectEntry, ProjectParser};
+use crate::query::Query;
+use crate::title;
+use crate::types::{MessageText, ProjectFile, Scopes, SearchResult};
+use iocraft::prelude::*;
+use smol::channel::{self, Receiver, Sender};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+/// How far a background load has got, in files and bytes.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LoadProgress {
+    /// Still listing the roots; totals are not known yet.
+    pub discovering: bool,
+    pub files_total: usize,
+    pub files_parsed: usize,
+    pub bytes_total: u64,
+    pub bytes_read: u64,
+}
+
+impl LoadProgress {
+    pub fn is_loading(&self) -> bool {
+        self.discovering || self.files_parsed < self.files_total
+    }
+
+    /// One-line status such as `Loading 12/30 files (3.4/10.2 MB)`.
+    pub fn describe(&self) -> String {
+        if self.discovering {
+            return "Discovering session files...".to_string();
+        }
+        format!(
+            "{} {}/{} files ({:.1}/{:.1} MB)",
+            if self.is_loading() { "Loading" } else { "Loaded" },
+            self.files_parsed,
+            self.files_total,
+            self.bytes_read as f64 / 1_000_000.0,
+            self.bytes_total as f64 / 1_000_000.0,
+        )
+    }
+}
+
+pub struct ProjectLoader;
+
+impl ProjectLoader {
+    /// Parses `entries` on `threads` workers from the blocking thread pool,
+    /// sending each file (with its size on disk) as soon as it has been read
+    /// and tokenized for the search index. Files arrive in completion order,
+    /// not in the order of `entries`.
+    pub fn spawn(
+        entries: Vec<ProjectEntry>,
+        threads: usize,
+        cache: Option<IndexCache>,
+    ) -> Receiver<(AnalyzedProject, u64)> {
+        let (sender, receiver) = channel::unbounded();
+        let entries = Arc::new(entries);
+        let next = Arc::new(AtomicUsize::new(0));
+        for _ in 0..threads.clamp(1, entries.len().max(1)) {
+            let (entries, next, sender, cache) = (entries.clone(), next.clone(), sender.clone(), cache.clone());
+            smol::unblock(move || {
+                while let Some(entry) = entries.get(next.fetch_add(1, Ordering::Relaxed)) {
+                    // 読み込めないファイルはスキップ
+                    if let Ok(project) = ProjectParser::load_entry(entry, cache.as_ref())
+                        && sender
+                            .send_blocking((SearchIndex::analyze(Arc::new(project)), entry.size))
+                            .is_err()
+                    {
+                        break;
+                    }
+                }
+            })
+            .detach();
+        }
+        receiver
+    }
+}
+
+/// Component state holding the projects loaded so far.
+///
+/// Files are discovered and parsed in the background; `projects` grows as
+/// each one arrives so the UI can render and search partial results.
+#[derive(Clone)]
+pub struct ProjectStore {
+    pub projects: State<Vec<Arc<ProjectFile>>>,
+    pub entries: State<Vec<ProjectEntry>>,
+    pub progress: State<LoadProgress>,
+    /// Full-text index over every project in `projects`.
+    pub index: State<SearchIndex>,
+    /// Bumped whenever `projects` changes, including in-place replacements.
+    pub revision: State<u64>,
+    /// Number of discovered files handed to the loader so far.
+    requested: State<usize>,
+    requests: Sender<Vec<ProjectEntry>>,
+    page_size: usize,
+}
+
+impl ProjectStore {
+    pub fn total_files(&self) -> usize {
+        self.entries.read().len()
+    }
+
+    /// Projects matching `query` in `mode` within `scopes`, best match first.
+    /// An empty query lists every project, newest first.
+    pub fn search(&self, query: &str, mode: SearchMode, scopes: Scopes) -> Result<Vec<SearchResult>, String> {
+        if mode == SearchMode::Query {
+            let query = Query::parse(query)?;
+            if !query.is_empty() {
+                return Ok(self.index.read().search(&query, scopes));
+            }
+        } else if !query.trim().is_empty() {
+            return Ok(Matcher::new(mode, query)?.search(&self.projects.read(), scopes));
+        }
+        Ok(self.projects.read().iter().cloned().map(SearchResult::all).collect())
+    }
+
+    /// Whether some discovered files are outside the loaded window.
+    pub fn has_more(&self) -> bool {
+        !self.progress.get().discovering && self.requested.get() < self.total_files()
+    }
+
+    /// Queues the next page of files for loading.
+    pub fn load_more(&self) {
+        if !self.has_more() {
+            return;
+        }
+        let entries = self.entries.read();
+        let start = self.requested.get();
+        let end = (start + self.page_size).min(entries.len());
+        let _ = self.requests.try_send(entries[start..end].to_vec());
+        let mut requested = self.requested;
+        requested.set(end);
+    }
+}
+
+pub trait UseProjectStore {
+    /// Starts loading the projects described by `options` in the background.
+    fn use_project_store(&mut self, options: &LoadOptions) -> ProjectStore;
+}
+
+impl UseProjectStore for Hooks<'_, '_> {
+    fn use_project_store(&mut self, options: &LoadOptions) -> ProjectStore {
+        let projects = self.use_state(Vec::<Arc<ProjectFile>>::new);
+        let entries = self.use_state(Vec::new);
+        let progress = self.use_state(|| LoadProgress {
+            discovering: true,
+            ..Default::default()
+        });
+        let index = self.use_state(SearchIndex::default);
+        let revision = self.use_state(|| 0u64);
+        let requested = self.use_state(|| 0);
+        let (requests, windows) = self.use_const(|| {
+            let (sender, receiver) = channel::unbounded::<Vec<ProjectEntry>>();
+            (sender, Arc::new(receiver))
+        });
+
+        self.use_future({
+            let roots = options.roots.clone();
+            let limit = options.limit;
+            let threads = options.threads;
+            let cache = options.cache();
+            let (mut projects, mut entries, mut progress, mut index, mut revision, mut requested) =
+                (projects, entries, progress, index, revision, requested);
+            let requests = requests.clone();
+            async move {
+                let found =
+                    smol::unblock(move || ProjectParser::discover_project_files(&roots).unwrap_or_default()).await;
+                if let Some(cache) = cache.clone() {
+                    // 古いエントリや消えたセッションのエントリを裏で掃除する
+                    smol::unblock(move || cache.prune()).detach();
+                }
+                let window = limit.map_or(found.len(), |limit| limit.min(found.len()));
+                let _ = requests.try_send(found[..window].to_vec());
+                requested.set(window);
+                entries.set(found);
+                progress.write().discovering = false;
+
+                while let Ok(window) = windows.recv().await {
+                    {
+                        let mut progress = progress.write();
+                        progress.files_total += window.len();
+                        progress.bytes_total += window.iter().map(|entry| entry.size).sum::<u64>();
+                    }
+                    let loaded = ProjectLoader::spawn(window, threads, cache.clone());
+                    while let Ok((analyzed, size)) = loaded.recv().await {
+                        let project = Arc::clone(&analyzed.project);
+                        index.write().insert(analyzed);
+                        // Keep the list newest first even though workers finish out of order
+                        let mut projects = projects.write();
+                        let at = projects.partition_point(|loaded| loaded.modified >= project.modified);
+                        projects.insert(at, project);
+                        drop(projects);
+                        revision += 1;
+                        let mut progress = progress.write();
+                        progress.files_parsed += 1;
+                        progress.bytes_read += size;
+                    }
+                    // 別のセッションのファイルに書かれた summary でタイトルが変わるセッションを差し替える
+                    let titles = {
+                        let projects = projects.read();
+                        let texts: Vec<&[MessageText]> = projects.iter().map(|project| project.texts.as_slice()).collect();
+                        title::session_titles(&texts)
+                    };
+                    let mut retitled = false;
+                    for (at, title) in titles.into_iter().enumerate() {
+                        let project = Arc::clone(&projects.read()[at]);
+                        if project.title != title {
+                            let project = Arc::new(ProjectFile { title, ..(*project).clone() });
+                            index.write().replace(Arc::clone(&project));
+                            projects.write()[at] = project;
+                            retitled = true;
+                        }
+                    }
+                    if retitled {
+                        revision += 1;
+                    }
+                    // Files that failed to open still count towards the window
+                    let mut progress = progress.write();
+                    progress.files_parsed = progress.files_total;
+                    progress.bytes_read = progress.bytes_total;
+                }
+            }
+        });
+
+        ProjectStore {
+            projects,
+            entries,
+            progress,
+            index,
+            revision,
+            requested,
+            requests,
+            page_size: options.page_size(),
+        }
+    }
+}
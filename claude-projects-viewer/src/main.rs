@@ -1,75 +1,139 @@
-mod parser;
-mod types;
-
+use claude_projects_viewer::config::LoadOptions;
+use claude_projects_viewer::export::{export_results, write_documents, ExportFormat};
+use claude_projects_viewer::group::ProjectGroup;
+use claude_projects_viewer::loader::UseProjectStore;
+use claude_projects_viewer::pricing::PricingTable;
+use claude_projects_viewer::matcher::{Matcher, SearchMode};
+use claude_projects_viewer::types::{Scope, Scopes};
+use claude_projects_viewer::stats::{ActivityStats, ToolStats, UsageStats};
+use claude_projects_viewer::snippet::{message_snippet, result_preview};
+use claude_projects_viewer::thread::Thread;
+use claude_projects_viewer::ui::{format_cost, format_datetime, format_time, format_tokens, visible_range, ActivityView, DiagnosticsView, HighlightedText, SessionsView, ThreadView, ToolsView, UsageView};
 use iocraft::prelude::*;
-use parser::ProjectParser;
-use types::SessionMessage;
+use std::path::Path;
+use std::sync::Arc;
 
-#[derive(Clone, Copy, PartialEq)]
+// Open views name their project or session by path rather than by position,
+// since the results are reordered while sessions are still loading.
+#[derive(Clone, PartialEq)]
 enum ViewMode {
     List,
-    Detail(usize),
+    /// Sessions of the project with this path
+    Sessions(String),
+    /// Matching messages of the session file at this path
+    Detail(String),
+    /// Conversation tree of the session file at this path
+    Thread(String),
+    Diagnostics,
+    /// Token usage of the sessions matching the search
+    Usage,
+    /// Tool calls in the sessions matching the search
+    Tools,
+    /// When the sessions matching the search took place
+    Activity,
 }
 
-#[derive(Clone)]
-struct SearchResult {
-    project_name: String,
-    messages: Vec<SessionMessage>,
+#[derive(Default, Props)]
+struct ClaudeProjectsViewerProps {
+    options: LoadOptions,
+    pricing: Arc<PricingTable>,
 }
 
 #[component]
-fn ClaudeProjectsViewer(mut hooks: Hooks) -> impl Into<AnyElement<'static>> {
+fn ClaudeProjectsViewer(mut hooks: Hooks, props: &ClaudeProjectsViewerProps) -> impl Into<AnyElement<'static>> {
     let (width, height) = hooks.use_terminal_size();
     let mut should_exit = hooks.use_state(|| false);
     
-    // Load all projects on startup
-    let projects = ProjectParser::load_all_projects().unwrap_or_default();
+    // Load session files in the background, growing the window on "load more"
+    let store = hooks.use_project_store(&props.options);
+    let projects = store.projects.read();
+    let progress = store.progress.get();
+    let total_files = store.total_files();
+    let has_more = store.has_more();
     
     let mut query = hooks.use_state(String::new);
     let mut selected_index = hooks.use_state(|| 0);
+    let mut session_index = hooks.use_state(|| 0usize);
     let mut view_mode = hooks.use_state(|| ViewMode::List);
     let mut search_has_focus = hooks.use_state(|| true);
+    let mut search_mode = hooks.use_state(SearchMode::default);
+    let mut search_scopes = hooks.use_state(Scopes::default);
+    let mut thread_leaf = hooks.use_state(|| None::<usize>);
+    let mut thread_cursor = hooks.use_state(|| 0usize);
+    // 直前の JSONL 書き出しの結果 (詳細画面のフッターに表示)
+    let mut export_status = hooks.use_state(|| None::<Result<String, String>>);
     
-    // Search results - only calculate when query changes
+    // Search results, ranked by relevance - only recalculated when the query or the loaded projects change
     let query_str = query.to_string();
-    let search_results: Vec<SearchResult> = if query_str.is_empty() {
-        // クエリがない場合は全プロジェクトを表示
-        projects.iter()
-            .map(|project| SearchResult {
-                project_name: project.project_name.clone(),
-                messages: project.messages.clone(),
-            })
-            .collect()
-    } else {
-        ProjectParser::search_messages(&projects, &query_str)
-            .into_iter()
-            .map(|(project_name, messages)| SearchResult {
-                project_name,
-                messages,
-            })
-            .collect()
-    };
+    let (search_results, matcher, query_error) = hooks.use_memo(
+        || match store.search(&query_str, search_mode.get(), search_scopes.get()) {
+            Ok(results) => (
+                Arc::new(results),
+                Arc::new(Matcher::new(search_mode.get(), &query_str).unwrap_or_default()),
+                None,
+            ),
+            Err(e) => (Arc::new(Vec::new()), Arc::new(Matcher::default()), Some(e)),
+        },
+        (&query_str, search_mode.get(), search_scopes.get(), store.revision.get()),
+    );
+    // 同じプロジェクトのセッションをまとめる
+    let groups = hooks.use_memo(
+        || Arc::new(ProjectGroup::group(&search_results)),
+        (&query_str, search_mode.get(), search_scopes.get(), store.revision.get()),
+    );
     
     // Reset selected index if it's out of bounds
-    if selected_index.get() >= search_results.len() && !search_results.is_empty() {
+    if selected_index.get() >= groups.len() && !groups.is_empty() {
         selected_index.set(0);
     }
     
-    // Create a state for results count that updates with search results
-    let mut results_count_state = hooks.use_state(|| search_results.len());
-    results_count_state.set(search_results.len());
+    // 画面に収まる行だけを描画する
+    let visible_rows = visible_range(selected_index.get(), groups.len(), (height.saturating_sub(20) / 3) as usize);
+    
+    // 表示中の画面。開いているプロジェクトやセッションは描画のたびにパスで引き直す
+    let view = view_mode.read().clone();
+    
+    // 開いているセッションの会話ツリーと、表示するブランチ。ツリーは
+    // セッションが替わるか、読み込みでメッセージが増えたときだけ作り直す
+    let open_session = match &view {
+        ViewMode::Detail(path) | ViewMode::Thread(path) => search_results.iter().find(|result| &result.project.path == path),
+        _ => None,
+    };
+    let thread = hooks.use_memo(
+        || open_session.map(|result| Arc::new(Thread::build(&result.project.texts))),
+        open_session.map(|result| (&result.project.path, result.project.texts.len())),
+    );
+    let branch = thread
+        .as_ref()
+        .and_then(|thread| thread_leaf.get().or_else(|| thread.main_leaf()).map(|leaf| thread.branch(leaf)))
+        .unwrap_or_default();
     
     // Handle keyboard events
     hooks.use_terminal_events({
-        let results_count = results_count_state.get();
+        let store = store.clone();
+        // Setting state on every render would re-render forever, so the
+        // handler captures the count directly
+        let results_count = groups.len();
+        let results = search_results.clone();
+        let groups = groups.clone();
+        let (thread, branch) = (thread.clone(), branch.clone());
         move |event| match event {
             TerminalEvent::Key(KeyEvent { code, kind, .. }) if kind != KeyEventKind::Release => {
+                // 書き込み中に読むと止まるので、キーごとに一度だけ読む
+                let view = view_mode.read().clone();
                 match code {
                     KeyCode::Esc => {
                         if search_has_focus.get() {
                             // 検索フォーカス時はフォーカスを外す
                             search_has_focus.set(false);
-                        } else if view_mode.get() != ViewMode::List {
+                        } else if let ViewMode::Thread(path) = &view {
+                            view_mode.set(ViewMode::Detail(path.clone()));
+                        } else if let ViewMode::Detail(path) = &view
+                            && let Some(result) = results.iter().find(|result| &result.project.path == path)
+                        {
+                            // 詳細からはセッション一覧に戻る
+                            view_mode.set(ViewMode::Sessions(result.project.project_path.clone()));
+                        } else if view != ViewMode::List {
                             view_mode.set(ViewMode::List);
                         } else {
                             should_exit.set(true);
@@ -79,26 +143,115 @@ fn ClaudeProjectsViewer(mut hooks: Hooks) -> impl Into<AnyElement<'static>> {
                         // Toggle focus between search and list
                         search_has_focus.set(!search_has_focus.get());
                     }
-                    KeyCode::Up => {
-                        // リストフォーカス時のみ動作
-                        if !search_has_focus.get() && view_mode.get() == ViewMode::List && results_count > 0 {
-                            let current = selected_index.get();
-                            selected_index.set(if current > 0 { current - 1 } else { results_count - 1 });
+                    KeyCode::Char(c @ '1'..='5') if !search_has_focus.get() && view == ViewMode::List => {
+                        // 検索スコープを切り替え (最後の1つは外さない)
+                        let mut scopes = search_scopes.get();
+                        scopes.toggle(Scope::ALL[c as usize - '1' as usize]);
+                        if !scopes.is_empty() {
+                            search_scopes.set(scopes);
                         }
                     }
-                    KeyCode::Down => {
+                    KeyCode::BackTab => {
+                        // 検索モードを切り替え (text → regex → fuzzy)
+                        search_mode.set(search_mode.get().next());
+                    }
+                    KeyCode::Up if !search_has_focus.get() && view == ViewMode::List && results_count > 0 => {
                         // リストフォーカス時のみ動作
-                        if !search_has_focus.get() && view_mode.get() == ViewMode::List && results_count > 0 {
-                            let current = selected_index.get();
-                            selected_index.set((current + 1) % results_count);
-                        }
+                        let current = selected_index.get();
+                        selected_index.set(if current > 0 { current - 1 } else { results_count - 1 });
+                    }
+                    KeyCode::Down if !search_has_focus.get() && view == ViewMode::List && results_count > 0 => {
+                        // リストフォーカス時のみ動作
+                        let current = selected_index.get();
+                        selected_index.set((current + 1) % results_count);
                     }
-                    KeyCode::Enter => {
+                    KeyCode::Enter if !search_has_focus.get() && view == ViewMode::List && results_count > 0 => {
                         // リストフォーカス時のみ動作
-                        if !search_has_focus.get() && view_mode.get() == ViewMode::List && results_count > 0 {
-                            view_mode.set(ViewMode::Detail(selected_index.get()));
+                        if let Some(group) = groups.get(selected_index.get()) {
+                            session_index.set(0);
+                            view_mode.set(ViewMode::Sessions(group.path.clone()));
+                        }
+                    }
+                    KeyCode::Up | KeyCode::Down if !search_has_focus.get() && matches!(view, ViewMode::Sessions(_)) => {
+                        if let ViewMode::Sessions(path) = &view {
+                            let count = groups.iter().find(|group| &group.path == path).map_or(0, |group| group.sessions.len()).max(1);
+                            let current = session_index.get();
+                            session_index.set(if code == KeyCode::Up { (current + count - 1) % count } else { (current + 1) % count });
+                        }
+                    }
+                    KeyCode::Enter if !search_has_focus.get() && matches!(view, ViewMode::Sessions(_)) => {
+                        // セッションのメッセージを表示
+                        if let ViewMode::Sessions(path) = &view
+                            && let Some(result) = groups
+                                .iter()
+                                .find(|group| &group.path == path)
+                                .and_then(|group| group.results(&results).nth(session_index.get()))
+                        {
+                            export_status.set(None);
+                            view_mode.set(ViewMode::Detail(result.project.path.clone()));
+                        }
+                    }
+                    KeyCode::Char('t') if !search_has_focus.get() => match &view {
+                        ViewMode::Detail(path) => {
+                            // 最初のヒットを含むブランチを開く
+                            if let Some(result) = results.iter().find(|result| &result.project.path == path)
+                                && let Some(thread) = &thread
+                            {
+                                let start = result.message_indices.iter().copied().find(|&i| thread.contains(i));
+                                thread_leaf.set(start.map(|message| thread.latest_leaf(message)));
+                                thread_cursor.set(start.map_or(0, |message| thread.depth(message)));
+                                view_mode.set(ViewMode::Thread(path.clone()));
+                            }
+                        }
+                        ViewMode::Thread(path) => view_mode.set(ViewMode::Detail(path.clone())),
+                        _ => {}
+                    },
+                    KeyCode::Char('x') if !search_has_focus.get() && matches!(view, ViewMode::Detail(_)) => {
+                        // 表示中のメッセージをセッション JSONL としてカレントディレクトリに書き出す
+                        if let ViewMode::Detail(path) = &view
+                            && let Some(result) = results.iter().find(|result| &result.project.path == path)
+                        {
+                            let written = export_results(std::slice::from_ref(result), ExportFormat::Jsonl)
+                                .and_then(|documents| write_documents(&documents, Path::new(".")));
+                            export_status.set(Some(written.map(|paths| {
+                                let paths: Vec<String> = paths.iter().map(|path| path.display().to_string()).collect();
+                                format!("Wrote {} messages to {}", result.len(), paths.join(", "))
+                            })));
+                        }
+                    }
+                    KeyCode::Up if !search_has_focus.get() && matches!(view, ViewMode::Thread(_)) => {
+                        thread_cursor.set(thread_cursor.get().saturating_sub(1));
+                    }
+                    KeyCode::Down if !search_has_focus.get() && matches!(view, ViewMode::Thread(_)) => {
+                        thread_cursor.set((thread_cursor.get() + 1).min(branch.len().saturating_sub(1)));
+                    }
+                    KeyCode::Left | KeyCode::Right if !search_has_focus.get() && matches!(view, ViewMode::Thread(_)) => {
+                        // 選択中のメッセージを別の続き (編集・再試行されたもの) に切り替える
+                        if let (Some(thread), Some(&message)) = (&thread, branch.get(thread_cursor.get())) {
+                            let alternative = thread.next_alternative(message, code == KeyCode::Right);
+                            thread_leaf.set(Some(thread.latest_leaf(alternative)));
                         }
                     }
+                    KeyCode::Char('d') if !search_has_focus.get() && view == ViewMode::List => {
+                        // パース診断画面を表示
+                        view_mode.set(ViewMode::Diagnostics);
+                    }
+                    KeyCode::Char('u') if !search_has_focus.get() && view == ViewMode::List => {
+                        // 検索結果のトークン使用量を表示
+                        view_mode.set(ViewMode::Usage);
+                    }
+                    KeyCode::Char('o') if !search_has_focus.get() && view == ViewMode::List => {
+                        // 検索結果のツール使用状況を表示
+                        view_mode.set(ViewMode::Tools);
+                    }
+                    KeyCode::Char('a') if !search_has_focus.get() && view == ViewMode::List => {
+                        // 検索結果のアクティビティを表示
+                        view_mode.set(ViewMode::Activity);
+                    }
+                    KeyCode::Char('m') if !search_has_focus.get() && view == ViewMode::List && has_more => {
+                        // リストフォーカス時は次のページを読み込む
+                        store.load_more();
+                    }
                     _ => {}
                 }
             }
@@ -114,7 +267,7 @@ fn ClaudeProjectsViewer(mut hooks: Hooks) -> impl Into<AnyElement<'static>> {
     
     element! {
         View(width: width, height: height, padding: 2) {
-            #(match view_mode.get() {
+            #(match &view {
                 ViewMode::List => {
                     element! {
                         View(flex_direction: FlexDirection::Column, width: 100pct) {
@@ -122,9 +275,9 @@ fn ClaudeProjectsViewer(mut hooks: Hooks) -> impl Into<AnyElement<'static>> {
                             View(margin_bottom: 1) {
                                 Text(
                                     content: if query_str.is_empty() {
-                                        format!("Claude Projects Viewer - Latest {} Projects", projects.len())
+                                        format!("Claude Projects Viewer - Latest {} of {} Sessions", projects.len(), total_files)
                                     } else {
-                                        format!("Claude Projects Viewer - Search Results")
+                                        "Claude Projects Viewer - Search Results".to_string()
                                     },
                                     color: Color::White,
                                     weight: Weight::Bold,
@@ -132,6 +285,15 @@ fn ClaudeProjectsViewer(mut hooks: Hooks) -> impl Into<AnyElement<'static>> {
                                 )
                             }
                             
+                            // Loading progress
+                            View(margin_bottom: 1) {
+                                Text(
+                                    content: progress.describe(),
+                                    color: if progress.is_loading() { Color::Yellow } else { Color::DarkGrey },
+                                    align: TextAlign::Center,
+                                )
+                            }
+                            
                             // Search Bar
                             View(
                                 border_style: BorderStyle::Round,
@@ -157,8 +319,11 @@ fn ClaudeProjectsViewer(mut hooks: Hooks) -> impl Into<AnyElement<'static>> {
                                         element! {
                                             View(margin_top: 1) {
                                                 Text(
-                                                    content: format!("Current query: {}", query_str),
-                                                    color: Color::Cyan,
+                                                    content: match &query_error {
+                                                        Some(e) => format!("Invalid query: {e}"),
+                                                        None => format!("Current query: {}", query_str),
+                                                    },
+                                                    color: if query_error.is_some() { Color::Red } else { Color::Cyan },
                                                 )
                                             }
                                         }
@@ -174,20 +339,38 @@ fn ClaudeProjectsViewer(mut hooks: Hooks) -> impl Into<AnyElement<'static>> {
                             View(margin_bottom: 1) {
                                 Text(
                                     content: if search_has_focus.get() {
-                                        "Type to search | ESC: unfocus search | Tab: toggle focus"
+                                        format!("Mode: {} | Type to search | Shift+Tab: switch mode | ESC: unfocus search | Tab: toggle focus", search_mode.get().label())
                                     } else {
-                                        "Tab: focus search | ↑/↓: navigate | Enter: view | ESC: exit"
+                                        "Tab: search | ↑/↓: move | Enter: open | 1-5: scopes | m: more | d/u/o/a: diagnostics/usage/tools/activity | ESC: exit".to_string()
                                     },
                                     color: Color::Grey,
                                 )
                             }
                             
+                            // Search scopes
+                            View(margin_bottom: 1) {
+                                Text(content: "Scopes: ", color: Color::Grey)
+                                #(Scope::ALL.into_iter().enumerate().map(|(i, scope)| {
+                                    let enabled = search_scopes.get().contains(scope);
+                                    element! {
+                                        Text(
+                                            content: format!("[{}] {} {}  ", if enabled { "x" } else { " " }, i + 1, scope.label()),
+                                            color: if enabled { Color::Cyan } else { Color::DarkGrey },
+                                        )
+                                    }
+                                }))
+                            }
+                            
                             // Search Results
                             #(if search_results.is_empty() {
                                 element! {
                                     View(padding: 2) {
                                         Text(
-                                            content: if query_str.is_empty() {
+                                            content: if progress.is_loading() {
+                                                "Loading projects..."
+                                            } else if query_error.is_some() {
+                                                "Fix the query to see results"
+                                            } else if query_str.is_empty() {
                                                 "No projects found"
                                             } else {
                                                 "No matching projects found"
@@ -211,39 +394,30 @@ fn ClaudeProjectsViewer(mut hooks: Hooks) -> impl Into<AnyElement<'static>> {
                                             border_color: Color::Grey,
                                             padding: 1,
                                         ) {
-                                            View(width: 40pct) {
+                                            View(width: 30pct) {
                                                 Text(content: "Project", weight: Weight::Bold, decoration: TextDecoration::Underline)
                                             }
-                                            View(width: 20pct) {
+                                            View(width: 12pct) {
                                                 Text(
                                                     content: if query_str.is_empty() { "Messages" } else { "Matches" },
                                                     weight: Weight::Bold,
                                                     decoration: TextDecoration::Underline
                                                 )
                                             }
-                                            View(width: 40pct) {
+                                            View(width: 28pct) {
+                                                Text(content: "Sessions · last activity · tokens", weight: Weight::Bold, decoration: TextDecoration::Underline)
+                                            }
+                                            View(width: 30pct) {
                                                 Text(content: "Preview", weight: Weight::Bold, decoration: TextDecoration::Underline)
                                             }
                                         }
                                         
                                         // Table Rows
-                                        #(search_results.iter().enumerate().map(|(i, result)| {
+                                        #(groups.iter().enumerate().skip(visible_rows.start).take(visible_rows.len()).map(|(i, group)| {
                                             let is_selected = i == selected_index.get();
-                                            let preview = result.messages.first()
-                                                .map(|msg| {
-                                                    let texts = msg.get_text_content();
-                                                    texts.first()
-                                                        .map(|text| {
-                                                            let text = text.chars().take(50).collect::<String>();
-                                                            if text.len() == 50 {
-                                                                format!("{text}...")
-                                                            } else {
-                                                                text
-                                                            }
-                                                        })
-                                                        .unwrap_or_else(|| "No preview available".to_string())
-                                                })
-                                                .unwrap_or_else(|| "No preview available".to_string());
+                                            let preview = result_preview(&search_results[group.sessions[0]], &matcher, search_scopes.get(), 30);
+                                            let message_count = group.message_count(&search_results);
+                                            let diagnostic_count = group.diagnostic_count(&search_results);
                                             
                                             element! {
                                                 View(
@@ -252,22 +426,47 @@ fn ClaudeProjectsViewer(mut hooks: Hooks) -> impl Into<AnyElement<'static>> {
                                                     border_style: if is_selected { BorderStyle::Round } else { BorderStyle::None },
                                                     border_color: if is_selected { Color::Green } else { Color::Reset },
                                                 ) {
-                                                    View(width: 40pct) {
+                                                    View(width: 30pct) {
                                                         Text(
-                                                            content: &result.project_name,
+                                                            content: &group.name,
                                                             color: if is_selected { Color::White } else { Color::Reset },
                                                             weight: if is_selected { Weight::Bold } else { Weight::Normal },
                                                         )
+                                                        #(if diagnostic_count > 0 {
+                                                            element! {
+                                                                Text(
+                                                                    content: format!(" ⚠ {}", diagnostic_count),
+                                                                    color: Color::Yellow,
+                                                                )
+                                                            }
+                                                        } else {
+                                                            element! { Text(content: "") }
+                                                        })
                                                     }
-                                                    View(width: 20pct) {
+                                                    View(width: 12pct) {
                                                         Text(
-                                                            content: format!("{}", result.messages.len()),
+                                                            content: match group.score(&search_results) {
+                                                                Some(score) => format!("{} (score {:.1})", message_count, score),
+                                                                None => format!("{}", message_count),
+                                                            },
                                                             color: Color::Cyan,
                                                         )
                                                     }
-                                                    View(width: 40pct) {
+                                                    View(width: 28pct) {
                                                         Text(
-                                                            content: &preview,
+                                                            content: format!(
+                                                                "{} · {} · {} tok",
+                                                                group.sessions.len(),
+                                                                format_time(group.last_activity(&search_results)),
+                                                                format_tokens(group.tokens(&search_results).total()),
+                                                            ),
+                                                            color: Color::Grey,
+                                                        )
+                                                    }
+                                                    View(width: 30pct) {
+                                                        HighlightedText(
+                                                            content: preview.text,
+                                                            highlights: preview.highlights,
                                                             color: Color::DarkGrey,
                                                         )
                                                     }
@@ -280,8 +479,95 @@ fn ClaudeProjectsViewer(mut hooks: Hooks) -> impl Into<AnyElement<'static>> {
                         }
                     }
                 }
-                ViewMode::Detail(index) => {
-                    if let Some(result) = search_results.get(index) {
+                ViewMode::Diagnostics => {
+                    let diagnostics: Vec<_> = projects
+                        .iter()
+                        .flat_map(|project| project.diagnostics.iter().cloned())
+                        .collect();
+                    element! {
+                        View(flex_direction: FlexDirection::Column, width: 100pct) {
+                            DiagnosticsView(diagnostics, height: Some(height.saturating_sub(10)))
+                            View(margin_top: 1) {
+                                Text(content: "Press ESC to go back", color: Color::Grey)
+                            }
+                        }
+                    }
+                }
+                ViewMode::Usage => {
+                    let stats = Arc::new(UsageStats::collect(&search_results, &props.pricing));
+                    element! {
+                        View(flex_direction: FlexDirection::Column, width: 100pct) {
+                            UsageView(stats, rows: Some((height.saturating_sub(30) / 2).max(3) as usize))
+                            View(margin_top: 1) {
+                                Text(content: "Press ESC to go back", color: Color::Grey)
+                            }
+                        }
+                    }
+                }
+                ViewMode::Tools => {
+                    let stats = Arc::new(ToolStats::collect(&search_results));
+                    element! {
+                        View(flex_direction: FlexDirection::Column, width: 100pct) {
+                            ToolsView(stats, rows: Some((height.saturating_sub(20) / 2).max(3) as usize))
+                            View(margin_top: 1) {
+                                Text(content: "Press ESC to go back", color: Color::Grey)
+                            }
+                        }
+                    }
+                }
+                ViewMode::Activity => {
+                    let stats = Arc::new(ActivityStats::collect(&search_results));
+                    element! {
+                        View(flex_direction: FlexDirection::Column, width: 100pct) {
+                            ActivityView(stats, rows: Some(height.saturating_sub(35).max(3) as usize))
+                            View(margin_top: 1) {
+                                Text(content: "Press ESC to go back", color: Color::Grey)
+                            }
+                        }
+                    }
+                }
+                ViewMode::Sessions(path) => {
+                    let group = groups.iter().find(|group| &group.path == path);
+                    let sessions: Vec<_> = group
+                        .map(|group| group.results(&search_results).cloned().collect())
+                        .unwrap_or_default();
+                    element! {
+                        View(flex_direction: FlexDirection::Column, width: 100pct) {
+                            SessionsView(
+                                project: group.map(|group| group.name.clone()).unwrap_or_default(),
+                                path: path.clone(),
+                                sessions,
+                                selected: session_index.get(),
+                                matcher: matcher.clone(),
+                                scopes: search_scopes.get(),
+                                pricing: props.pricing.clone(),
+                                height,
+                            )
+                        }
+                    }
+                }
+                ViewMode::Thread(path) => {
+                    let result = search_results.iter().find(|result| &result.project.path == path);
+                    element! {
+                        View(flex_direction: FlexDirection::Column, width: 100pct) {
+                            ThreadView(
+                                project: result.map(|result| Arc::clone(&result.project)),
+                                thread: thread.clone().unwrap_or_default(),
+                                branch: branch.clone(),
+                                cursor: thread_cursor.get(),
+                                matches: match result {
+                                    Some(result) if !query_str.trim().is_empty() => result.message_indices.clone(),
+                                    _ => Vec::new(),
+                                },
+                                matcher: matcher.clone(),
+                                scopes: search_scopes.get(),
+                                height,
+                            )
+                        }
+                    }
+                }
+                ViewMode::Detail(path) => {
+                    if let Some(result) = search_results.iter().find(|result| &result.project.path == path) {
                         element! {
                             View(
                                 flex_direction: FlexDirection::Column,
@@ -297,16 +583,20 @@ fn ClaudeProjectsViewer(mut hooks: Hooks) -> impl Into<AnyElement<'static>> {
                                     View(flex_direction: FlexDirection::Column) {
                                         View(margin_bottom: 1) {
                                             Text(
-                                                content: &result.project_name,
+                                                content: &result.project.short_name,
                                                 color: Color::White,
                                                 weight: Weight::Bold,
                                             )
+                                            Text(
+                                                content: format!(" · {}", result.project.label()),
+                                                color: Color::Grey,
+                                            )
                                         }
                                         Text(
                                             content: if query_str.is_empty() {
-                                                format!("{} messages in project", result.messages.len())
+                                                format!("{} messages in session {}", result.len(), result.project.session_id())
                                             } else {
-                                                format!("{} messages matching search", result.messages.len())
+                                                format!("{} messages matching search", result.len())
                                             },
                                             color: Color::Cyan,
                                         )
@@ -319,14 +609,11 @@ fn ClaudeProjectsViewer(mut hooks: Hooks) -> impl Into<AnyElement<'static>> {
                                     height: height.saturating_sub(10),
                                     overflow: Overflow::Scroll,
                                 ) {
-                                    #(result.messages.iter().enumerate().map(|(i, msg)| {
-                                        let msg_type = msg.get_type();
-                                        let timestamp = msg.get_timestamp().unwrap_or("N/A");
-                                        let content_preview = msg.get_text_content()
-                                            .join(" ")
-                                            .chars()
-                                            .take(200)
-                                            .collect::<String>();
+                                    #(result.texts().enumerate().map(|(i, msg)| {
+                                        let msg_type = msg.kind.as_str();
+                                        let timestamp = format_datetime(msg.time);
+                                        let matched_in = result.scopes.get(i).copied();
+                                        let content_preview = message_snippet(msg, &matcher, search_scopes.get(), 100, 5);
                                         
                                         element! {
                                             View(
@@ -356,9 +643,22 @@ fn ClaudeProjectsViewer(mut hooks: Hooks) -> impl Into<AnyElement<'static>> {
                                                             content: format!(" - {}", timestamp),
                                                             color: Color::DarkGrey,
                                                         )
+                                                        #(msg.tokens.map(|tokens| element! {
+                                                            Text(
+                                                                content: match props.pricing.message_cost(msg) {
+                                                                    Some(cost) => format!(" · {} tok · {}", format_tokens(tokens.total()), format_cost(cost)),
+                                                                    None => format!(" · {} tok", format_tokens(tokens.total())),
+                                                                },
+                                                                color: Color::Yellow,
+                                                            )
+                                                        }))
+                                                        #(matched_in.map(|scopes| element! {
+                                                            Text(content: format!(" · matched in {}", scopes.describe()), color: Color::Magenta)
+                                                        }))
                                                     }
-                                                    Text(
-                                                        content: &content_preview,
+                                                    HighlightedText(
+                                                        content: content_preview.text,
+                                                        highlights: content_preview.highlights,
                                                         wrap: TextWrap::Wrap,
                                                     )
                                                 }
@@ -369,7 +669,15 @@ fn ClaudeProjectsViewer(mut hooks: Hooks) -> impl Into<AnyElement<'static>> {
                                 
                                 // Footer
                                 View(margin_top: 1) {
-                                    Text(content: "Press ESC to go back", color: Color::Grey)
+                                    Text(content: "t: conversation tree | x: export JSONL | Press ESC to go back", color: Color::Grey)
+                                    #(export_status.read().clone().map(|status| element! {
+                                        Text(
+                                            content: match &status {
+                                                Ok(message) | Err(message) => format!(" · {}", message),
+                                            },
+                                            color: if status.is_ok() { Color::Green } else { Color::Red },
+                                        )
+                                    }))
                                 }
                             }
                         }
@@ -399,5 +707,19 @@ fn main() {
         }
     }));
     
-    smol::block_on(element!(ClaudeProjectsViewer).fullscreen()).unwrap();
+    let (options, args) = LoadOptions::from_env_args();
+    // 打ち間違えたオプション (--limt など) を黙って無視しない
+    if let Some(arg) = args.first() {
+        eprintln!("unrecognized argument: {}", arg);
+        eprintln!("usage: claude-projects-viewer {}", LoadOptions::USAGE);
+        std::process::exit(2);
+    }
+    let pricing = match options.pricing() {
+        Ok(pricing) => pricing,
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(2);
+        }
+    };
+    smol::block_on(element!(ClaudeProjectsViewer(options: options, pricing: Arc::new(pricing))).fullscreen()).unwrap();
 }
\ No newline at end of file
@@ -1,80 +1,154 @@
-mod parser;
-mod types;
-
+use claude_projects_viewer::config::LoadOptions;
+use claude_projects_viewer::export::{export_results, write_documents, ExportFormat};
+use claude_projects_viewer::group::ProjectGroup;
+use claude_projects_viewer::loader::UseProjectStore;
+use claude_projects_viewer::parser::ProjectParser;
+use claude_projects_viewer::pricing::PricingTable;
+use claude_projects_viewer::matcher::{Matcher, SearchMode};
+use claude_projects_viewer::types::{ParseDiagnostic, Scope, Scopes, SearchResult};
+use claude_projects_viewer::stats::{ActivityStats, ToolStats, UsageStats};
+use claude_projects_viewer::snippet::{message_snippet, result_preview};
+use claude_projects_viewer::thread::Thread;
+use claude_projects_viewer::ui::{format_cost, format_time, format_tokens, visible_range, ActivityView, DiagnosticsView, HighlightedText, ResultsReport, SessionReport, SessionsView, ThreadView, ToolsView, UsageView};
 use iocraft::prelude::*;
-use parser::ProjectParser;
-use types::SessionMessage;
-use std::env;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
 
-#[derive(Clone, Copy, PartialEq)]
+// Open views name their project or session by path rather than by position,
+// since the results are reordered while sessions are still loading.
+#[derive(Clone, PartialEq)]
 enum ViewMode {
     List,
-    Detail(usize),
+    /// Sessions of the project with this path
+    Sessions(String),
+    /// Matching messages of the session file at this path
+    Detail(String),
+    /// Conversation tree of the session file at this path
+    Thread(String),
+    Diagnostics,
+    /// Token usage of the sessions matching the search
+    Usage,
+    /// Tool calls in the sessions matching the search
+    Tools,
+    /// When the sessions matching the search took place
+    Activity,
+}
+
+/// What was on screen when Ctrl+P was pressed, printed once the fullscreen
+/// UI has been left.
+struct Printout {
+    view_mode: ViewMode,
+    results: Arc<Vec<SearchResult>>,
+    query: String,
+    search_mode: SearchMode,
+    scopes: Scopes,
+    diagnostics: Vec<ParseDiagnostic>,
 }
 
-#[derive(Clone)]
-struct SearchResult {
-    project_name: String,
-    messages: Vec<SessionMessage>,
+#[derive(Default, Props)]
+struct ClaudeProjectsViewerProps {
+    options: LoadOptions,
+    pricing: Arc<PricingTable>,
+    /// Filled by Ctrl+P before exiting
+    printout: Arc<Mutex<Option<Printout>>>,
 }
 
 #[component]
-fn ClaudeProjectsViewer(mut hooks: Hooks) -> impl Into<AnyElement<'static>> {
+fn ClaudeProjectsViewer(mut hooks: Hooks, props: &ClaudeProjectsViewerProps) -> impl Into<AnyElement<'static>> {
     let (width, height) = hooks.use_terminal_size();
     let mut should_exit = hooks.use_state(|| false);
     
-    // Load all projects on startup
-    let projects = ProjectParser::load_all_projects().unwrap_or_default();
+    // Load session files in the background, growing the window on "load more"
+    let store = hooks.use_project_store(&props.options);
+    let projects = store.projects.read();
+    let progress = store.progress.get();
+    let total_files = store.total_files();
+    let has_more = store.has_more();
     
     let mut query = hooks.use_state(String::new);
     let mut selected_index = hooks.use_state(|| 0);
+    let mut session_index = hooks.use_state(|| 0usize);
     let mut view_mode = hooks.use_state(|| ViewMode::List);
     let mut search_has_focus = hooks.use_state(|| true);
+    let mut search_mode = hooks.use_state(SearchMode::default);
+    let mut search_scopes = hooks.use_state(Scopes::default);
+    let mut thread_leaf = hooks.use_state(|| None::<usize>);
+    let mut thread_cursor = hooks.use_state(|| 0usize);
+    // 直前の JSONL 書き出しの結果 (詳細画面のフッターに表示)
+    let mut export_status = hooks.use_state(|| None::<Result<String, String>>);
     
-    // Search results - only calculate when query changes
+    // Search results, ranked by relevance - only recalculated when the query or the loaded projects change
     let query_str = query.to_string();
-    let search_results: Vec<SearchResult> = if query_str.is_empty() {
-        // クエリがない場合は全プロジェクトを表示
-        projects.iter()
-            .map(|project| SearchResult {
-                project_name: project.project_name.clone(),
-                messages: project.messages.clone(),
-            })
-            .collect()
-    } else {
-        ProjectParser::search_messages(&projects, &query_str)
-            .into_iter()
-            .map(|(project_name, messages)| SearchResult {
-                project_name,
-                messages,
-            })
-            .collect()
-    };
+    let (search_results, matcher, query_error) = hooks.use_memo(
+        || match store.search(&query_str, search_mode.get(), search_scopes.get()) {
+            Ok(results) => (
+                Arc::new(results),
+                Arc::new(Matcher::new(search_mode.get(), &query_str).unwrap_or_default()),
+                None,
+            ),
+            Err(e) => (Arc::new(Vec::new()), Arc::new(Matcher::default()), Some(e)),
+        },
+        (&query_str, search_mode.get(), search_scopes.get(), store.revision.get()),
+    );
+    // 同じプロジェクトのセッションをまとめる
+    let groups = hooks.use_memo(
+        || Arc::new(ProjectGroup::group(&search_results)),
+        (&query_str, search_mode.get(), search_scopes.get(), store.revision.get()),
+    );
     
     // Reset selected index if it's out of bounds
-    if selected_index.get() >= search_results.len() && !search_results.is_empty() {
+    if selected_index.get() >= groups.len() && !groups.is_empty() {
         selected_index.set(0);
     }
     
-    // Create a state for results count that updates with search results
-    let mut results_count_state = hooks.use_state(|| search_results.len());
-    results_count_state.set(search_results.len());
+    // 画面に収まる行だけを描画する
+    let visible_rows = visible_range(selected_index.get(), groups.len(), (height.saturating_sub(20) / 3) as usize);
     
-    // Store query and results length for keyboard handler
-    let query_for_handler = query_str.clone();
-    let results_len_for_handler = search_results.len();
+    // 表示中の画面。開いているプロジェクトやセッションは描画のたびにパスで引き直す
+    let view = view_mode.read().clone();
+    
+    // 開いているセッションの会話ツリーと、表示するブランチ。ツリーは
+    // セッションが替わるか、読み込みでメッセージが増えたときだけ作り直す
+    let open_session = match &view {
+        ViewMode::Detail(path) | ViewMode::Thread(path) => search_results.iter().find(|result| &result.project.path == path),
+        _ => None,
+    };
+    let thread = hooks.use_memo(
+        || open_session.map(|result| Arc::new(Thread::build(&result.project.texts))),
+        open_session.map(|result| (&result.project.path, result.project.texts.len())),
+    );
+    let branch = thread
+        .as_ref()
+        .and_then(|thread| thread_leaf.get().or_else(|| thread.main_leaf()).map(|leaf| thread.branch(leaf)))
+        .unwrap_or_default();
     
     // Handle keyboard events
     hooks.use_terminal_events({
-        let results_count = results_count_state.get();
+        let store = store.clone();
+        // Setting state on every render would re-render forever, so the
+        // handler captures the count directly
+        let results_count = groups.len();
+        let results = search_results.clone();
+        let groups = groups.clone();
+        let (thread, branch) = (thread.clone(), branch.clone());
+        let printout = props.printout.clone();
         move |event| match event {
             TerminalEvent::Key(KeyEvent { code, kind, modifiers, .. }) if kind != KeyEventKind::Release => {
+                // 書き込み中に読むと止まるので、キーごとに一度だけ読む
+                let view = view_mode.read().clone();
                 match code {
                     KeyCode::Esc => {
                         if search_has_focus.get() {
                             // 検索フォーカス時はフォーカスを外す
                             search_has_focus.set(false);
-                        } else if view_mode.get() != ViewMode::List {
+                        } else if let ViewMode::Thread(path) = &view {
+                            view_mode.set(ViewMode::Detail(path.clone()));
+                        } else if let ViewMode::Detail(path) = &view
+                            && let Some(result) = results.iter().find(|result| &result.project.path == path)
+                        {
+                            // 詳細からはセッション一覧に戻る
+                            view_mode.set(ViewMode::Sessions(result.project.project_path.clone()));
+                        } else if view != ViewMode::List {
                             view_mode.set(ViewMode::List);
                         } else {
                             should_exit.set(true);
@@ -84,49 +158,133 @@ fn ClaudeProjectsViewer(mut hooks: Hooks) -> impl Into<AnyElement<'static>> {
                         // Toggle focus between search and list
                         search_has_focus.set(!search_has_focus.get());
                     }
-                    KeyCode::Up => {
-                        // リストフォーカス時のみ動作
-                        if !search_has_focus.get() && view_mode.get() == ViewMode::List && results_count > 0 {
-                            let current = selected_index.get();
-                            selected_index.set(if current > 0 { current - 1 } else { results_count - 1 });
+                    KeyCode::Char(c @ '1'..='5') if !search_has_focus.get() && view == ViewMode::List => {
+                        // 検索スコープを切り替え (最後の1つは外さない)
+                        let mut scopes = search_scopes.get();
+                        scopes.toggle(Scope::ALL[c as usize - '1' as usize]);
+                        if !scopes.is_empty() {
+                            search_scopes.set(scopes);
                         }
                     }
-                    KeyCode::Down => {
+                    KeyCode::BackTab => {
+                        // 検索モードを切り替え (text → regex → fuzzy)
+                        search_mode.set(search_mode.get().next());
+                    }
+                    KeyCode::Up if !search_has_focus.get() && view == ViewMode::List && results_count > 0 => {
                         // リストフォーカス時のみ動作
-                        if !search_has_focus.get() && view_mode.get() == ViewMode::List && results_count > 0 {
-                            let current = selected_index.get();
-                            selected_index.set((current + 1) % results_count);
-                        }
+                        let current = selected_index.get();
+                        selected_index.set(if current > 0 { current - 1 } else { results_count - 1 });
+                    }
+                    KeyCode::Down if !search_has_focus.get() && view == ViewMode::List && results_count > 0 => {
+                        // リストフォーカス時のみ動作
+                        let current = selected_index.get();
+                        selected_index.set((current + 1) % results_count);
                     }
-                    KeyCode::Enter => {
+                    KeyCode::Enter if !search_has_focus.get() && view == ViewMode::List && results_count > 0 => {
                         // リストフォーカス時のみ動作
-                        if !search_has_focus.get() && view_mode.get() == ViewMode::List && results_count > 0 {
-                            view_mode.set(ViewMode::Detail(selected_index.get()));
+                        if let Some(group) = groups.get(selected_index.get()) {
+                            session_index.set(0);
+                            view_mode.set(ViewMode::Sessions(group.path.clone()));
                         }
                     }
-                    KeyCode::Char('p') | KeyCode::Char('P') => {
-                        // Ctrl+P で現在の画面を静的出力
-                        if modifiers.contains(KeyModifiers::CONTROL) {
-                            // 現在の画面を標準出力に出力して終了
-                            println!("\n=== Claude Projects Viewer Output ===\n");
-                            
-                            if let ViewMode::List = view_mode.get() {
-                                // List view
-                                println!("Search: {}", query_for_handler);
-                                println!("Results: {} projects\n", results_len_for_handler);
-                                
-                                // Note: Can't iterate over search_results here, just show count
-                                println!("(Use arrow keys to navigate and Enter to view details)")
-                            } else if let ViewMode::Detail(_) = view_mode.get() {
-                                // Detail view
-                                println!("Detail view selected");
-                                println!("(Cannot access detail content in export mode)")
+                    KeyCode::Up | KeyCode::Down if !search_has_focus.get() && matches!(view, ViewMode::Sessions(_)) => {
+                        if let ViewMode::Sessions(path) = &view {
+                            let count = groups.iter().find(|group| &group.path == path).map_or(0, |group| group.sessions.len()).max(1);
+                            let current = session_index.get();
+                            session_index.set(if code == KeyCode::Up { (current + count - 1) % count } else { (current + 1) % count });
+                        }
+                    }
+                    KeyCode::Enter if !search_has_focus.get() && matches!(view, ViewMode::Sessions(_)) => {
+                        // セッションのメッセージを表示
+                        if let ViewMode::Sessions(path) = &view
+                            && let Some(result) = groups
+                                .iter()
+                                .find(|group| &group.path == path)
+                                .and_then(|group| group.results(&results).nth(session_index.get()))
+                        {
+                            export_status.set(None);
+                            view_mode.set(ViewMode::Detail(result.project.path.clone()));
+                        }
+                    }
+                    KeyCode::Char('t') if !search_has_focus.get() => match &view {
+                        ViewMode::Detail(path) => {
+                            // 最初のヒットを含むブランチを開く
+                            if let Some(result) = results.iter().find(|result| &result.project.path == path)
+                                && let Some(thread) = &thread
+                            {
+                                let start = result.message_indices.iter().copied().find(|&i| thread.contains(i));
+                                thread_leaf.set(start.map(|message| thread.latest_leaf(message)));
+                                thread_cursor.set(start.map_or(0, |message| thread.depth(message)));
+                                view_mode.set(ViewMode::Thread(path.clone()));
                             }
-                            
-                            println!("\n=== End of Output ===");
-                            should_exit.set(true);
+                        }
+                        ViewMode::Thread(path) => view_mode.set(ViewMode::Detail(path.clone())),
+                        _ => {}
+                    },
+                    KeyCode::Char('x') if !search_has_focus.get() && matches!(view, ViewMode::Detail(_)) => {
+                        // 表示中のメッセージをセッション JSONL としてカレントディレクトリに書き出す
+                        if let ViewMode::Detail(path) = &view
+                            && let Some(result) = results.iter().find(|result| &result.project.path == path)
+                        {
+                            let written = export_results(std::slice::from_ref(result), ExportFormat::Jsonl)
+                                .and_then(|documents| write_documents(&documents, Path::new(".")));
+                            export_status.set(Some(written.map(|paths| {
+                                let paths: Vec<String> = paths.iter().map(|path| path.display().to_string()).collect();
+                                format!("Wrote {} messages to {}", result.len(), paths.join(", "))
+                            })));
+                        }
+                    }
+                    KeyCode::Up if !search_has_focus.get() && matches!(view, ViewMode::Thread(_)) => {
+                        thread_cursor.set(thread_cursor.get().saturating_sub(1));
+                    }
+                    KeyCode::Down if !search_has_focus.get() && matches!(view, ViewMode::Thread(_)) => {
+                        thread_cursor.set((thread_cursor.get() + 1).min(branch.len().saturating_sub(1)));
+                    }
+                    KeyCode::Left | KeyCode::Right if !search_has_focus.get() && matches!(view, ViewMode::Thread(_)) => {
+                        // 選択中のメッセージを別の続き (編集・再試行されたもの) に切り替える
+                        if let (Some(thread), Some(&message)) = (&thread, branch.get(thread_cursor.get())) {
+                            let alternative = thread.next_alternative(message, code == KeyCode::Right);
+                            thread_leaf.set(Some(thread.latest_leaf(alternative)));
                         }
                     }
+                    KeyCode::Char('d') if !search_has_focus.get() && view == ViewMode::List => {
+                        // パース診断画面を表示
+                        view_mode.set(ViewMode::Diagnostics);
+                    }
+                    KeyCode::Char('u') if !search_has_focus.get() && view == ViewMode::List => {
+                        // 検索結果のトークン使用量を表示
+                        view_mode.set(ViewMode::Usage);
+                    }
+                    KeyCode::Char('o') if !search_has_focus.get() && view == ViewMode::List => {
+                        // 検索結果のツール使用状況を表示
+                        view_mode.set(ViewMode::Tools);
+                    }
+                    KeyCode::Char('a') if !search_has_focus.get() && view == ViewMode::List => {
+                        // 検索結果のアクティビティを表示
+                        view_mode.set(ViewMode::Activity);
+                    }
+                    KeyCode::Char('m') if !search_has_focus.get() && view == ViewMode::List && has_more => {
+                        // リストフォーカス時は次のページを読み込む
+                        store.load_more();
+                    }
+                    KeyCode::Char('p') | KeyCode::Char('P') if modifiers.contains(KeyModifiers::CONTROL) => {
+                        // Ctrl+P: 全画面を抜けてから、表示中の一覧やセッションを print 版と同じ描画で出力する
+                        let diagnostics = store
+                            .projects
+                            .read()
+                            .iter()
+                            .flat_map(|project| project.diagnostics.iter().cloned())
+                            .collect();
+                        *printout.lock().unwrap() = Some(Printout {
+                            view_mode: view.clone(),
+                            results: results.clone(),
+                            query: query.to_string(),
+                            search_mode: search_mode.get(),
+                            scopes: search_scopes.get(),
+                            diagnostics,
+                        });
+                        should_exit.set(true);
+                    }
                     _ => {}
                 }
             }
@@ -142,20 +300,29 @@ fn ClaudeProjectsViewer(mut hooks: Hooks) -> impl Into<AnyElement<'static>> {
     
     element! {
         View(width: width, height: height, padding: 2) {
-            #(match view_mode.get() {
+            #(match &view {
                 ViewMode::List => {
                     element! {
                         View(flex_direction: FlexDirection::Column, width: 100pct) {
                             // Title
                             View(margin_bottom: 1) {
                                 Text(
-                                    content: format!("Claude Projects Viewer (Latest {} projects)", projects.len()),
+                                    content: format!("Claude Projects Viewer (Latest {} of {} sessions)", projects.len(), total_files),
                                     color: Color::White,
                                     weight: Weight::Bold,
                                     align: TextAlign::Center,
                                 )
                             }
                             
+                            // Loading progress
+                            View(margin_bottom: 1) {
+                                Text(
+                                    content: progress.describe(),
+                                    color: if progress.is_loading() { Color::Yellow } else { Color::DarkGrey },
+                                    align: TextAlign::Center,
+                                )
+                            }
+                            
                             // Search Bar
                             View(
                                 border_style: BorderStyle::Round,
@@ -182,8 +349,11 @@ fn ClaudeProjectsViewer(mut hooks: Hooks) -> impl Into<AnyElement<'static>> {
                                         element! {
                                             View(margin_top: 1) {
                                                 Text(
-                                                    content: format!("Current query: {}", query_str),
-                                                    color: Color::Cyan,
+                                                    content: match &query_error {
+                                                        Some(e) => format!("Invalid query: {e}"),
+                                                        None => format!("Current query: {}", query_str),
+                                                    },
+                                                    color: if query_error.is_some() { Color::Red } else { Color::Cyan },
                                                 )
                                             }
                                         }
@@ -199,20 +369,38 @@ fn ClaudeProjectsViewer(mut hooks: Hooks) -> impl Into<AnyElement<'static>> {
                             View(margin_bottom: 1) {
                                 Text(
                                     content: if search_has_focus.get() {
-                                        "Type to search | ESC: unfocus | Tab: toggle | Ctrl+P: export & exit"
+                                        format!("Mode: {} | Type to search | Shift+Tab: switch mode | ESC: unfocus | Tab: toggle | Ctrl+P: print & exit", search_mode.get().label())
                                     } else {
-                                        "Tab: focus search | ↑/↓: navigate | Enter: view | ESC: exit | Ctrl+P: export"
+                                        "Tab: search | ↑/↓: move | Enter: open | 1-5: scopes | m: more | d/u/o/a: diag/usage/tools/activity | ESC: exit | ^P: print".to_string()
                                     },
                                     color: Color::Grey,
                                 )
                             }
                             
+                            // Search scopes
+                            View(margin_bottom: 1) {
+                                Text(content: "Scopes: ", color: Color::Grey)
+                                #(Scope::ALL.into_iter().enumerate().map(|(i, scope)| {
+                                    let enabled = search_scopes.get().contains(scope);
+                                    element! {
+                                        Text(
+                                            content: format!("[{}] {} {}  ", if enabled { "x" } else { " " }, i + 1, scope.label()),
+                                            color: if enabled { Color::Cyan } else { Color::DarkGrey },
+                                        )
+                                    }
+                                }))
+                            }
+                            
                             // Search Results
                             #(if search_results.is_empty() {
                                 element! {
                                     View(padding: 2) {
                                         Text(
-                                            content: if query_str.is_empty() {
+                                            content: if progress.is_loading() {
+                                                "Loading projects..."
+                                            } else if query_error.is_some() {
+                                                "Fix the query to see results"
+                                            } else if query_str.is_empty() {
                                                 "No projects found"
                                             } else {
                                                 "No matching projects found"
@@ -236,39 +424,30 @@ fn ClaudeProjectsViewer(mut hooks: Hooks) -> impl Into<AnyElement<'static>> {
                                             border_color: Color::Grey,
                                             padding: 1,
                                         ) {
-                                            View(width: 40pct) {
+                                            View(width: 30pct) {
                                                 Text(content: "Project", weight: Weight::Bold, decoration: TextDecoration::Underline)
                                             }
-                                            View(width: 20pct) {
+                                            View(width: 12pct) {
                                                 Text(
-                                                    content: if query_str.is_empty() { "Total Messages" } else { "Found" },
+                                                    content: if query_str.is_empty() { "Messages" } else { "Found" },
                                                     weight: Weight::Bold,
                                                     decoration: TextDecoration::Underline
                                                 )
                                             }
-                                            View(width: 40pct) {
+                                            View(width: 28pct) {
+                                                Text(content: "Sessions · last activity · tokens", weight: Weight::Bold, decoration: TextDecoration::Underline)
+                                            }
+                                            View(width: 30pct) {
                                                 Text(content: "Preview", weight: Weight::Bold, decoration: TextDecoration::Underline)
                                             }
                                         }
                                         
                                         // Table Rows
-                                        #(search_results.iter().enumerate().map(|(i, result)| {
+                                        #(groups.iter().enumerate().skip(visible_rows.start).take(visible_rows.len()).map(|(i, group)| {
                                             let is_selected = i == selected_index.get();
-                                            let preview = result.messages.first()
-                                                .map(|msg| {
-                                                    let texts = msg.get_text_content();
-                                                    texts.first()
-                                                        .map(|text| {
-                                                            let text = text.chars().take(50).collect::<String>();
-                                                            if text.len() == 50 {
-                                                                format!("{text}...")
-                                                            } else {
-                                                                text
-                                                            }
-                                                        })
-                                                        .unwrap_or_else(|| "No preview available".to_string())
-                                                })
-                                                .unwrap_or_else(|| "No preview available".to_string());
+                                            let preview = result_preview(&search_results[group.sessions[0]], &matcher, search_scopes.get(), 30);
+                                            let message_count = group.message_count(&search_results);
+                                            let diagnostic_count = group.diagnostic_count(&search_results);
                                             
                                             element! {
                                                 View(
@@ -277,26 +456,51 @@ fn ClaudeProjectsViewer(mut hooks: Hooks) -> impl Into<AnyElement<'static>> {
                                                     border_style: if is_selected { BorderStyle::Round } else { BorderStyle::None },
                                                     border_color: if is_selected { Color::Green } else { Color::Reset },
                                                 ) {
-                                                    View(width: 40pct) {
+                                                    View(width: 30pct) {
                                                         Text(
-                                                            content: &result.project_name,
+                                                            content: &group.name,
                                                             color: if is_selected { Color::White } else { Color::Reset },
                                                             weight: if is_selected { Weight::Bold } else { Weight::Normal },
                                                         )
+                                                        #(if diagnostic_count > 0 {
+                                                            element! {
+                                                                Text(
+                                                                    content: format!(" ⚠ {}", diagnostic_count),
+                                                                    color: Color::Yellow,
+                                                                )
+                                                            }
+                                                        } else {
+                                                            element! { Text(content: "") }
+                                                        })
                                                     }
-                                                    View(width: 20pct) {
+                                                    View(width: 12pct) {
                                                         Text(
                                                             content: if query_str.is_empty() {
-                                                                format!("{} messages", result.messages.len())
+                                                                format!("{} messages", message_count)
                                                             } else {
-                                                                format!("{} found", result.messages.len())
+                                                                match group.score(&search_results) {
+                                                                    Some(score) => format!("{} found (score {:.1})", message_count, score),
+                                                                    None => format!("{} found", message_count),
+                                                                }
                                                             },
                                                             color: Color::Cyan,
                                                         )
                                                     }
-                                                    View(width: 40pct) {
+                                                    View(width: 28pct) {
                                                         Text(
-                                                            content: &preview,
+                                                            content: format!(
+                                                                "{} · {} · {} tok",
+                                                                group.sessions.len(),
+                                                                format_time(group.last_activity(&search_results)),
+                                                                format_tokens(group.tokens(&search_results).total()),
+                                                            ),
+                                                            color: Color::Grey,
+                                                        )
+                                                    }
+                                                    View(width: 30pct) {
+                                                        HighlightedText(
+                                                            content: preview.text,
+                                                            highlights: preview.highlights,
                                                             color: Color::DarkGrey,
                                                         )
                                                     }
@@ -309,8 +513,95 @@ fn ClaudeProjectsViewer(mut hooks: Hooks) -> impl Into<AnyElement<'static>> {
                         }
                     }
                 }
-                ViewMode::Detail(index) => {
-                    if let Some(result) = search_results.get(index) {
+                ViewMode::Diagnostics => {
+                    let diagnostics: Vec<_> = projects
+                        .iter()
+                        .flat_map(|project| project.diagnostics.iter().cloned())
+                        .collect();
+                    element! {
+                        View(flex_direction: FlexDirection::Column, width: 100pct) {
+                            DiagnosticsView(diagnostics, height: Some(height.saturating_sub(10)))
+                            View(margin_top: 1) {
+                                Text(content: "Press ESC to go back", color: Color::Grey)
+                            }
+                        }
+                    }
+                }
+                ViewMode::Usage => {
+                    let stats = Arc::new(UsageStats::collect(&search_results, &props.pricing));
+                    element! {
+                        View(flex_direction: FlexDirection::Column, width: 100pct) {
+                            UsageView(stats, rows: Some((height.saturating_sub(30) / 2).max(3) as usize))
+                            View(margin_top: 1) {
+                                Text(content: "Press ESC to go back", color: Color::Grey)
+                            }
+                        }
+                    }
+                }
+                ViewMode::Tools => {
+                    let stats = Arc::new(ToolStats::collect(&search_results));
+                    element! {
+                        View(flex_direction: FlexDirection::Column, width: 100pct) {
+                            ToolsView(stats, rows: Some((height.saturating_sub(20) / 2).max(3) as usize))
+                            View(margin_top: 1) {
+                                Text(content: "Press ESC to go back", color: Color::Grey)
+                            }
+                        }
+                    }
+                }
+                ViewMode::Activity => {
+                    let stats = Arc::new(ActivityStats::collect(&search_results));
+                    element! {
+                        View(flex_direction: FlexDirection::Column, width: 100pct) {
+                            ActivityView(stats, rows: Some(height.saturating_sub(35).max(3) as usize))
+                            View(margin_top: 1) {
+                                Text(content: "Press ESC to go back", color: Color::Grey)
+                            }
+                        }
+                    }
+                }
+                ViewMode::Sessions(path) => {
+                    let group = groups.iter().find(|group| &group.path == path);
+                    let sessions: Vec<_> = group
+                        .map(|group| group.results(&search_results).cloned().collect())
+                        .unwrap_or_default();
+                    element! {
+                        View(flex_direction: FlexDirection::Column, width: 100pct) {
+                            SessionsView(
+                                project: group.map(|group| group.name.clone()).unwrap_or_default(),
+                                path: path.clone(),
+                                sessions,
+                                selected: session_index.get(),
+                                matcher: matcher.clone(),
+                                scopes: search_scopes.get(),
+                                pricing: props.pricing.clone(),
+                                height,
+                            )
+                        }
+                    }
+                }
+                ViewMode::Thread(path) => {
+                    let result = search_results.iter().find(|result| &result.project.path == path);
+                    element! {
+                        View(flex_direction: FlexDirection::Column, width: 100pct) {
+                            ThreadView(
+                                project: result.map(|result| Arc::clone(&result.project)),
+                                thread: thread.clone().unwrap_or_default(),
+                                branch: branch.clone(),
+                                cursor: thread_cursor.get(),
+                                matches: match result {
+                                    Some(result) if !query_str.trim().is_empty() => result.message_indices.clone(),
+                                    _ => Vec::new(),
+                                },
+                                matcher: matcher.clone(),
+                                scopes: search_scopes.get(),
+                                height,
+                            )
+                        }
+                    }
+                }
+                ViewMode::Detail(path) => {
+                    if let Some(result) = search_results.iter().find(|result| &result.project.path == path) {
                         element! {
                             View(
                                 flex_direction: FlexDirection::Column,
@@ -326,13 +617,17 @@ fn ClaudeProjectsViewer(mut hooks: Hooks) -> impl Into<AnyElement<'static>> {
                                     View(flex_direction: FlexDirection::Column) {
                                         View(margin_bottom: 1) {
                                             Text(
-                                                content: &result.project_name,
+                                                content: &result.project.short_name,
                                                 color: Color::White,
                                                 weight: Weight::Bold,
                                             )
+                                            Text(
+                                                content: format!(" · {}", result.project.label()),
+                                                color: Color::Grey,
+                                            )
                                         }
                                         Text(
-                                            content: format!("{} messages found", result.messages.len()),
+                                            content: format!("{} messages found in session {}", result.len(), result.project.session_id()),
                                             color: Color::Cyan,
                                         )
                                     }
@@ -344,13 +639,10 @@ fn ClaudeProjectsViewer(mut hooks: Hooks) -> impl Into<AnyElement<'static>> {
                                     height: height.saturating_sub(10),
                                     overflow: Overflow::Scroll,
                                 ) {
-                                    #(result.messages.iter().enumerate().map(|(i, msg)| {
-                                        let msg_type = msg.get_type();
-                                        let content_preview = msg.get_text_content()
-                                            .join(" ")
-                                            .chars()
-                                            .take(200)
-                                            .collect::<String>();
+                                    #(result.texts().enumerate().map(|(i, msg)| {
+                                        let msg_type = msg.kind.as_str();
+                                        let matched_in = result.scopes.get(i).copied();
+                                        let content_preview = message_snippet(msg, &matcher, search_scopes.get(), 100, 5);
                                         
                                         element! {
                                             View(
@@ -376,9 +668,22 @@ fn ClaudeProjectsViewer(mut hooks: Hooks) -> impl Into<AnyElement<'static>> {
                                                             },
                                                             weight: Weight::Bold,
                                                         )
+                                                        #(msg.tokens.map(|tokens| element! {
+                                                            Text(
+                                                                content: match props.pricing.message_cost(msg) {
+                                                                    Some(cost) => format!(" · {} tok · {}", format_tokens(tokens.total()), format_cost(cost)),
+                                                                    None => format!(" · {} tok", format_tokens(tokens.total())),
+                                                                },
+                                                                color: Color::Yellow,
+                                                            )
+                                                        }))
+                                                        #(matched_in.map(|scopes| element! {
+                                                            Text(content: format!(" · matched in {}", scopes.describe()), color: Color::Magenta)
+                                                        }))
                                                     }
-                                                    Text(
-                                                        content: &content_preview,
+                                                    HighlightedText(
+                                                        content: content_preview.text,
+                                                        highlights: content_preview.highlights,
                                                         wrap: TextWrap::Wrap,
                                                     )
                                                 }
@@ -389,7 +694,15 @@ fn ClaudeProjectsViewer(mut hooks: Hooks) -> impl Into<AnyElement<'static>> {
                                 
                                 // Footer
                                 View(margin_top: 1) {
-                                    Text(content: "Press ESC to go back | Ctrl+P to export", color: Color::Grey)
+                                    Text(content: "t: conversation tree | x: export JSONL | Press ESC to go back | Ctrl+P to print", color: Color::Grey)
+                                    #(export_status.read().clone().map(|status| element! {
+                                        Text(
+                                            content: match &status {
+                                                Ok(message) | Err(message) => format!(" · {}", message),
+                                            },
+                                            color: if status.is_ok() { Color::Green } else { Color::Red },
+                                        )
+                                    }))
                                 }
                             }
                         }
@@ -408,8 +721,21 @@ fn ClaudeProjectsViewer(mut hooks: Hooks) -> impl Into<AnyElement<'static>> {
 
 fn main() {
     // コマンドライン引数をチェック
-    let args: Vec<String> = env::args().collect();
-    let print_mode = args.get(1).map(|s| s == "--print").unwrap_or(false);
+    let (options, args) = LoadOptions::from_env_args();
+    let pricing = match options.pricing() {
+        Ok(pricing) => pricing,
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(2);
+        }
+    };
+    // --print 以外の引数 (--limt などの打ち間違い) は受け付けない
+    if let Some(arg) = args.iter().find(|s| *s != "--print") {
+        eprintln!("unrecognized argument: {}", arg);
+        eprintln!("usage: claude-projects-viewer-hybrid [--print] {}", LoadOptions::USAGE);
+        std::process::exit(2);
+    }
+    let print_mode = args.iter().any(|s| s == "--print");
     
     if print_mode {
         // 静的出力モード
@@ -417,26 +743,27 @@ fn main() {
         println!("Run without --print for interactive mode\n");
         
         // プロジェクトを読み込んで表示
-        match ProjectParser::load_all_projects() {
+        match ProjectParser::load_all_projects(&options) {
             Ok(projects) => {
-                println!("Loaded {} projects\n", projects.len());
+                let results: Vec<SearchResult> = projects.into_iter().map(|project| SearchResult::all(Arc::new(project))).collect();
+                let groups = ProjectGroup::group(&results);
+                println!("Loaded {} sessions in {} projects\n", results.len(), groups.len());
                 
-                for (i, project) in projects.iter().enumerate() {
-                    println!("{}. {} ({} messages)", 
+                for (i, group) in groups.iter().enumerate() {
+                    println!("{}. {} ({} sessions, {} messages)", 
                         i + 1, 
-                        project.project_name, 
-                        project.messages.len()
+                        group.name, 
+                        group.sessions.len(),
+                        group.message_count(&results)
                     );
                     
-                    if let Some(first_msg) = project.messages.first() {
-                        let preview = first_msg.get_text_content()
-                            .first()
-                            .map(|text| text.chars().take(80).collect::<String>())
-                            .unwrap_or_else(|| "No preview".to_string());
-                        println!("   {}", preview);
+                    for result in group.results(&results) {
+                        let preview = result_preview(result, &Matcher::default(), Scopes::default(), 80);
+                        println!("   {} ({})", result.project.label(), result.project.session_id());
+                        println!("     {}", preview.text);
                     }
                     
-                    if i < projects.len() - 1 {
+                    if i < groups.len() - 1 {
                         println!();
                     }
                 }
@@ -448,6 +775,46 @@ fn main() {
         }
     } else {
         // インタラクティブモード
-        smol::block_on(element!(ClaudeProjectsViewer).fullscreen()).unwrap();
+        let pricing = Arc::new(pricing);
+        let printout = Arc::new(Mutex::new(None));
+        smol::block_on(element!(ClaudeProjectsViewer(options: options, pricing: pricing.clone(), printout: printout.clone())).fullscreen()).unwrap();
+        
+        // 全画面の出力は消えるので、終了後に標準出力へ描画する
+        if let Some(printout) = printout.lock().unwrap().take() {
+            print_screen(printout, &pricing);
+        }
+    }
+}
+
+/// Prints the view Ctrl+P was pressed in with the renderers of
+/// `claude-projects-viewer-print`: the whole list, the sessions of the open
+/// project, or the full selected session.
+fn print_screen(printout: Printout, pricing: &Arc<PricingTable>) {
+    let matcher = Arc::new(Matcher::new(printout.search_mode, &printout.query).unwrap_or_default());
+    let results = printout.results;
+    match printout.view_mode {
+        ViewMode::List => element! {
+            ResultsReport(results, query: printout.query, matcher, scopes: printout.scopes, pricing: pricing.clone())
+        }
+        .print(),
+        ViewMode::Sessions(path) => {
+            let sessions: Vec<SearchResult> = ProjectGroup::group(&results)
+                .iter()
+                .find(|group| group.path == path)
+                .map(|group| group.results(&results).cloned().collect())
+                .unwrap_or_default();
+            element! {
+                ResultsReport(results: Arc::new(sessions), query: printout.query, matcher, scopes: printout.scopes, pricing: pricing.clone())
+            }
+            .print()
+        }
+        ViewMode::Detail(path) | ViewMode::Thread(path) => element! {
+            SessionReport(result: results.iter().find(|result| result.project.path == path).cloned(), matcher, scopes: printout.scopes, pricing: pricing.clone())
+        }
+        .print(),
+        ViewMode::Diagnostics => element!(DiagnosticsView(diagnostics: printout.diagnostics)).print(),
+        ViewMode::Usage => element!(UsageView(stats: Arc::new(UsageStats::collect(&results, pricing)))).print(),
+        ViewMode::Tools => element!(ToolsView(stats: Arc::new(ToolStats::collect(&results)))).print(),
+        ViewMode::Activity => element!(ActivityView(stats: Arc::new(ActivityStats::collect(&results)))).print(),
     }
 }
\ No newline at end of file
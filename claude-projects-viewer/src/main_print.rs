@@ -1,50 +1,193 @@
-mod parser;
-mod types;
-
+use claude_projects_viewer::config::LoadOptions;
+use claude_projects_viewer::export::{export_results, write_documents, ExportFormat};
+use claude_projects_viewer::index::SearchIndex;
+use claude_projects_viewer::matcher::{Matcher, SearchMode};
+use claude_projects_viewer::parser::ProjectParser;
+use claude_projects_viewer::query::Query;
+use claude_projects_viewer::stats::{ActivityStats, ToolStats, UsageStats};
+use claude_projects_viewer::types::{ProjectFile, Scopes, SearchResult};
+use claude_projects_viewer::ui::{ActivityView, DiagnosticsView, ResultsReport, ToolsView, UsageView};
 use iocraft::prelude::*;
-use parser::ProjectParser;
-use types::SessionMessage;
-use std::env;
-
-#[derive(Clone)]
-struct SearchResult {
-    project_name: String,
-    messages: Vec<SessionMessage>,
-}
+use std::sync::Arc;
 
 fn main() {
     // コマンドライン引数から検索クエリを取得
-    let args: Vec<String> = env::args().collect();
-    let query = args.get(1).map(|s| s.as_str()).unwrap_or("");
+    let (options, args) = LoadOptions::from_env_args();
+    let pricing = match options.pricing() {
+        Ok(pricing) => pricing,
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(2);
+        }
+    };
+    let diagnostics_mode = args.iter().any(|s| s == "--diagnostics");
+    let json_output = args.iter().any(|s| s == "--json");
+    let args: Vec<String> = args.into_iter().filter(|s| s != "--diagnostics" && s != "--json").collect();
+    
+    // stats / tools / activity [query]: 検索にヒットしたセッションのトークン使用量・ツール使用状況・活動時間を集計する
+    // export [query]: ヒットしたメッセージをセッションごとに書き出す
+    let mut report = None;
+    // --scope text,tool-input,... で検索対象を絞る
+    let mut scopes = Scopes::default();
+    // --csv days|hours|sessions: activity の集計を CSV で出力
+    let mut csv_table = None;
+    // --format markdown / --output DIR: export の形式と書き出し先
+    let mut export_format = ExportFormat::default();
+    let mut output_dir = None;
+    let mut query_args = Vec::new();
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        if arg == "--csv" {
+            match args.next().as_deref() {
+                Some(table @ ("days" | "hours" | "sessions")) => csv_table = Some(table.to_string()),
+                _ => {
+                    eprintln!("--csv requires one of: days, hours, sessions");
+                    std::process::exit(2);
+                }
+            }
+        } else if arg == "--format" {
+            export_format = match ExportFormat::parse(&args.next().unwrap_or_default()) {
+                Ok(format) => format,
+                Err(e) => {
+                    eprintln!("{}", e);
+                    std::process::exit(2);
+                }
+            };
+        } else if arg == "--output" {
+            match args.next() {
+                Some(dir) => output_dir = Some(std::path::PathBuf::from(dir)),
+                None => {
+                    eprintln!("--output requires a directory");
+                    std::process::exit(2);
+                }
+            }
+        } else if arg == "--scope" {
+            let list = args.next().unwrap_or_default();
+            scopes = match Scopes::parse(&list) {
+                Ok(parsed) if !parsed.is_empty() => parsed,
+                Ok(_) => {
+                    eprintln!("--scope requires at least one of: text, tool-input, tool-output, thinking, system");
+                    std::process::exit(2);
+                }
+                Err(e) => {
+                    eprintln!("{}", e);
+                    std::process::exit(2);
+                }
+            };
+        } else if arg.starts_with("--") {
+            // 打ち間違えたオプションを検索語として扱わない
+            eprintln!("unrecognized argument: {}", arg);
+            eprintln!("usage: claude-projects-viewer-print [options] [stats | tools | activity | export] [query...]");
+            std::process::exit(2);
+        } else if report.is_none()
+            && query_args.is_empty()
+            && matches!(arg.as_str(), "stats" | "tools" | "activity" | "export")
+        {
+            // オプションの後でも、最初の語ならサブコマンドとみなす
+            report = Some(arg);
+        } else {
+            query_args.push(arg);
+        }
+    }
+    // 残りの引数はすべてクエリとして扱う (例: type:user tool:Bash rust)
+    let query = query_args.join(" ");
+    let parsed_query = match Query::parse(&query) {
+        Ok(parsed) => parsed,
+        Err(e) => {
+            eprintln!("Invalid query: {}", e);
+            std::process::exit(2);
+        }
+    };
+    let matcher = Matcher::new(SearchMode::Query, &query).unwrap_or_default();
+    let query = query.as_str();
     
-    // Load all projects
-    let projects = match ProjectParser::load_all_projects() {
-        Ok(projects) => projects,
+    // Load the requested window of projects
+    let entries = match ProjectParser::discover_project_files(&options.roots) {
+        Ok(entries) => entries,
         Err(e) => {
             eprintln!("Error loading projects: {}", e);
             std::process::exit(1);
         }
     };
+    let total_files = entries.len();
+    let window = options.limit.map_or(total_files, |limit| limit.min(total_files));
+    let (projects, load_stats) = ProjectParser::load_projects_with_stats(&entries[..window], options.threads, options.cache().as_ref());
     
-    // Search results
-    let search_results: Vec<SearchResult> = if query.is_empty() {
-        // クエリがない場合は全プロジェクトを表示
-        projects.iter()
-            .map(|project| SearchResult {
-                project_name: project.project_name.clone(),
-                messages: project.messages.clone(),
-            })
-            .collect()
+    // --diagnostics: 読み込めなかった行の一覧を出力
+    if diagnostics_mode {
+        let diagnostics: Vec<_> = projects
+            .iter()
+            .flat_map(|project| project.diagnostics.iter().cloned())
+            .collect();
+        element!(DiagnosticsView(diagnostics)).print();
+        return;
+    }
+    
+    // Search results, best match first
+    let projects: Vec<Arc<ProjectFile>> = projects.into_iter().map(Arc::new).collect();
+    let search_results = if parsed_query.is_empty() {
+        projects.iter().cloned().map(SearchResult::all).collect()
     } else {
-        ProjectParser::search_messages(&projects, query)
-            .into_iter()
-            .map(|(project_name, messages)| SearchResult {
-                project_name,
-                messages,
-            })
-            .collect()
+        SearchIndex::build(&projects).search(&parsed_query, scopes)
     };
     
+    match report.as_deref() {
+        Some("stats") => {
+            let stats = Arc::new(UsageStats::collect(&search_results, &pricing));
+            element!(UsageView(stats)).print();
+            return;
+        }
+        Some("tools") => {
+            let stats = ToolStats::collect(&search_results);
+            if json_output {
+                println!("{}", serde_json::to_string_pretty(&stats).unwrap());
+            } else {
+                element!(ToolsView(stats: Arc::new(stats))).print();
+            }
+            return;
+        }
+        Some("activity") => {
+            let stats = ActivityStats::collect(&search_results);
+            match csv_table.as_deref() {
+                Some("days") => print!("{}", stats.days_csv()),
+                Some("hours") => print!("{}", stats.hours_csv()),
+                Some("sessions") => print!("{}", stats.sessions_csv()),
+                _ if json_output => println!("{}", serde_json::to_string_pretty(&stats).unwrap()),
+                _ => element!(ActivityView(stats: Arc::new(stats))).print(),
+            }
+            return;
+        }
+        Some("export") => {
+            let documents = match export_results(&search_results, export_format) {
+                Ok(documents) => documents,
+                Err(e) => {
+                    eprintln!("{}", e);
+                    std::process::exit(1);
+                }
+            };
+            match &output_dir {
+                Some(dir) => match write_documents(&documents, dir) {
+                    Ok(paths) => {
+                        for path in paths {
+                            println!("{}", path.display());
+                        }
+                    }
+                    Err(e) => {
+                        eprintln!("{}", e);
+                        std::process::exit(1);
+                    }
+                },
+                None => {
+                    for document in documents {
+                        print!("{}", document.content);
+                    }
+                }
+            }
+            return;
+        }
+        _ => {}
+    }
+    
     // Create UI
     let mut ui = element! {
         View(flex_direction: FlexDirection::Column, padding: 1) {
@@ -57,11 +200,16 @@ fn main() {
             ) {
                 View(flex_direction: FlexDirection::Column) {
                     Text(
-                        content: format!("Claude Projects Viewer (Latest {} projects)", projects.len()),
+                        content: format!("Claude Projects Viewer (Latest {} of {} sessions)", projects.len(), total_files),
                         color: Color::White,
                         weight: Weight::Bold,
                         align: TextAlign::Center,
                     )
+                    Text(
+                        content: load_stats.describe(),
+                        color: Color::DarkGrey,
+                        align: TextAlign::Center,
+                    )
                     #(if !query.is_empty() {
                         element! {
                             View(margin_top: 1) {
@@ -79,141 +227,13 @@ fn main() {
             }
             
             // Results
-            #(if search_results.is_empty() {
-                element! {
-                    View(padding: 2) {
-                        Text(
-                            content: if query.is_empty() {
-                                "No projects found"
-                            } else {
-                                "No matching projects found"
-                            },
-                            color: Color::Yellow,
-                            align: TextAlign::Center,
-                        )
-                    }
-                }
-            } else {
-                element! {
-                    View(flex_direction: FlexDirection::Column) {
-                        // Summary
-                        View(margin_bottom: 1) {
-                            Text(
-                                content: if query.is_empty() {
-                                    format!("Showing all {} projects", search_results.len())
-                                } else {
-                                    format!("Found {} matching projects", search_results.len())
-                                },
-                                color: Color::Cyan,
-                            )
-                        }
-                        
-                        // Results list
-                        View(flex_direction: FlexDirection::Column) {
-                            #(search_results.iter().enumerate().map(|(i, result)| {
-                                let total_messages = result.messages.len();
-                                let preview = result.messages.first()
-                                    .map(|msg| {
-                                        let texts = msg.get_text_content();
-                                        texts.first()
-                                            .map(|text| {
-                                                let text = text.chars().take(60).collect::<String>();
-                                                if text.len() == 60 {
-                                                    format!("{text}...")
-                                                } else {
-                                                    text
-                                                }
-                                            })
-                                            .unwrap_or_else(|| "No preview available".to_string())
-                                    })
-                                    .unwrap_or_else(|| "No preview available".to_string());
-                                
-                                element! {
-                                    View(
-                                        border_style: BorderStyle::Single,
-                                        border_color: Color::DarkGrey,
-                                        padding: 1,
-                                        margin_bottom: 1,
-                                    ) {
-                                        View(flex_direction: FlexDirection::Column) {
-                                            // Project name and message count
-                                            View(flex_direction: FlexDirection::Row, margin_bottom: 1) {
-                                                Text(
-                                                    content: format!("{}. ", i + 1),
-                                                    color: Color::DarkGrey,
-                                                )
-                                                Text(
-                                                    content: &result.project_name,
-                                                    color: Color::White,
-                                                    weight: Weight::Bold,
-                                                )
-                                                Text(
-                                                    content: format!(
-                                                        " ({} {})",
-                                                        total_messages,
-                                                        if query.is_empty() { "messages" } else { "matches" }
-                                                    ),
-                                                    color: Color::Cyan,
-                                                )
-                                            }
-                                            // Preview
-                                            Text(
-                                                content: format!("  {}", preview),
-                                                color: Color::DarkGrey,
-                                            )
-                                            // Show first few matches if searching
-                                            #(if !query.is_empty() && result.messages.len() > 0 {
-                                                element! {
-                                                    View(margin_top: 1, padding_left: 2) {
-                                                        View(flex_direction: FlexDirection::Column) {
-                                                            #(result.messages.iter().take(3).enumerate().map(|(j, msg)| {
-                                                                let msg_type = msg.get_type();
-                                                                let content = msg.get_text_content()
-                                                                    .join(" ")
-                                                                    .chars()
-                                                                    .take(100)
-                                                                    .collect::<String>();
-                                                                
-                                                                element! {
-                                                                    View(margin_bottom: if j < 2 { 1 } else { 0 }) {
-                                                                        Text(
-                                                                            content: format!("• [{}] {}", msg_type, content),
-                                                                            color: match msg_type {
-                                                                                "user" => Color::Green,
-                                                                                "assistant" => Color::Blue,
-                                                                                "system" => Color::Yellow,
-                                                                                _ => Color::Grey,
-                                                                            },
-                                                                        )
-                                                                    }
-                                                                }
-                                                            }))
-                                                            #(if result.messages.len() > 3 {
-                                                                element! {
-                                                                    View(margin_top: 1) {
-                                                                        Text(
-                                                                            content: format!("  ... and {} more matches", result.messages.len() - 3),
-                                                                            color: Color::DarkGrey,
-                                                                        )
-                                                                    }
-                                                                }
-                                                            } else {
-                                                                element! { View {} }
-                                                            })
-                                                        }
-                                                    }
-                                                }
-                                            } else {
-                                                element! { View {} }
-                                            })
-                                        }
-                                    }
-                                }
-                            }))
-                        }
-                    }
-                }
-            })
+            ResultsReport(
+                results: Arc::new(search_results),
+                query: query.to_string(),
+                matcher: Arc::new(matcher),
+                scopes,
+                pricing: Arc::new(pricing),
+            )
             
             // Usage help
             View(
@@ -224,11 +244,31 @@ fn main() {
             ) {
                 View(flex_direction: FlexDirection::Column) {
                     Text(content: "Usage:", color: Color::Yellow, weight: Weight::Bold)
-                    Text(content: "  claude-projects-viewer-print [search_query]", color: Color::DarkGrey)
+                    Text(content: "  claude-projects-viewer-print [--root DIR]... [--limit N | --all] [--threads N] [--diagnostics] [--scope LIST] [--pricing FILE] [query...]", color: Color::DarkGrey)
+                    Text(content: "  claude-projects-viewer-print [options] stats [query...]", color: Color::DarkGrey)
+                    Text(content: "  claude-projects-viewer-print [options] tools [--json] [query...]", color: Color::DarkGrey)
+                    Text(content: "  claude-projects-viewer-print [options] activity [--json | --csv days|hours|sessions] [query...]", color: Color::DarkGrey)
+                    Text(content: "  claude-projects-viewer-print [options] export [--format markdown|html|jsonl] [--output DIR] [query...]", color: Color::DarkGrey)
+                    Text(content: "", color: Color::DarkGrey)
+                    Text(content: "Query syntax:", color: Color::Yellow)
+                    Text(content: "  word \"exact phrase\" -excluded a OR b (grouped terms)", color: Color::DarkGrey)
+                    Text(content: "  type: tool: model: branch: project: path: session: after:YYYY-MM-DD before:YYYY-MM-DD", color: Color::DarkGrey)
                     Text(content: "", color: Color::DarkGrey)
                     Text(content: "Examples:", color: Color::Yellow)
-                    Text(content: "  claude-projects-viewer-print          # Show all projects", color: Color::DarkGrey)
-                    Text(content: "  claude-projects-viewer-print rust     # Search for 'rust'", color: Color::DarkGrey)
+                    Text(content: "  claude-projects-viewer-print              # Show the latest 30 projects", color: Color::DarkGrey)
+                    Text(content: "  claude-projects-viewer-print rust         # Search for 'rust'", color: Color::DarkGrey)
+                    Text(content: "  claude-projects-viewer-print --all rust   # Search every session file", color: Color::DarkGrey)
+                    Text(content: "  claude-projects-viewer-print tool:Bash after:2026-01-01  # Filter by metadata", color: Color::DarkGrey)
+                    Text(content: "  claude-projects-viewer-print --limit 100  # Show the latest 100 projects", color: Color::DarkGrey)
+                    Text(content: "  claude-projects-viewer-print --scope tool-input,tool-output cargo  # Search tool calls only", color: Color::DarkGrey)
+                    Text(content: "  claude-projects-viewer-print --diagnostics # List lines that failed to parse", color: Color::DarkGrey)
+                    Text(content: "  claude-projects-viewer-print stats project:repo  # Token usage of one project", color: Color::DarkGrey)
+                    Text(content: "  claude-projects-viewer-print tools --json  # Tool calls and failure rates as JSON", color: Color::DarkGrey)
+                    Text(content: "  claude-projects-viewer-print activity --csv days after:2026-01-01  # Messages per day as CSV", color: Color::DarkGrey)
+                    Text(content: "  claude-projects-viewer-print export --output notes session:1234  # One session as Markdown", color: Color::DarkGrey)
+                    Text(content: "  claude-projects-viewer-print export --format html project:repo > repo.html  # Shareable HTML page", color: Color::DarkGrey)
+                    Text(content: "  claude-projects-viewer-print export --format jsonl --output trimmed -type:system  # Trimmed session files", color: Color::DarkGrey)
+                    Text(content: "  claude-projects-viewer-print --all --threads 1  # Parse sequentially to compare timings", color: Color::DarkGrey)
                 }
             }
         }
@@ -0,0 +1,283 @@
+use crate::index::{token_spans, tokenize};
+use crate::query::Query;
+use crate::types::{MessageText, ProjectFile, Scope, Scopes, SearchResult};
+use regex::{Regex, RegexBuilder};
+use std::ops::Range;
+use std::sync::Arc;
+
+/// How the text typed into the search bar is interpreted.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum SearchMode {
+    /// The query language, ranked with the full-text index.
+    #[default]
+    Query,
+    /// A regular expression, case-insensitive unless it contains uppercase.
+    Regex,
+    /// fzf-style subsequence matching that tolerates a few typos.
+    Fuzzy,
+}
+
+impl SearchMode {
+    /// The mode after this one, cycling back to the first.
+    pub fn next(self) -> Self {
+        match self {
+            SearchMode::Query => SearchMode::Regex,
+            SearchMode::Regex => SearchMode::Fuzzy,
+            SearchMode::Fuzzy => SearchMode::Query,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            SearchMode::Query => "text",
+            SearchMode::Regex => "regex",
+            SearchMode::Fuzzy => "fuzzy",
+        }
+    }
+}
+
+/// Finds what a search matched inside a piece of text, for highlighting and
+/// for the regex and fuzzy modes, which the index cannot answer.
+#[derive(Debug, Clone)]
+pub enum Matcher {
+    /// Words from a query; indexed words starting with one of them match.
+    Terms(Vec<String>),
+    Regex(Regex),
+    /// Lowercased words of a fuzzy pattern, each matched on its own.
+    Fuzzy(Vec<Vec<char>>),
+}
+
+impl Default for Matcher {
+    fn default() -> Self {
+        Matcher::Terms(Vec::new())
+    }
+}
+
+impl Matcher {
+    pub fn new(mode: SearchMode, query: &str) -> Result<Self, String> {
+        match mode {
+            SearchMode::Query => {
+                let query = Query::parse(query)?;
+                let mut terms: Vec<String> = query.positive_text().into_iter().flat_map(tokenize).collect();
+                terms.sort();
+                terms.dedup();
+                Ok(Matcher::Terms(terms))
+            }
+            SearchMode::Regex => RegexBuilder::new(query)
+                // Smart case, like ripgrep
+                .case_insensitive(!has_uppercase_literal(query))
+                .build()
+                .map(Matcher::Regex)
+                .map_err(|e| e.to_string()),
+            SearchMode::Fuzzy => Ok(Matcher::Fuzzy(
+                query
+                    .split_whitespace()
+                    .map(|word| word.chars().flat_map(char::to_lowercase).collect())
+                    .collect(),
+            )),
+        }
+    }
+
+    /// Byte ranges of `text` to highlight, sorted and not overlapping.
+    pub fn find(&self, text: &str) -> Vec<Range<usize>> {
+        let ranges: Vec<Range<usize>> = match self {
+            Matcher::Terms(terms) => token_spans(text)
+                .into_iter()
+                .filter(|(_, token)| terms.iter().any(|term| token.starts_with(term.as_str())))
+                .map(|(span, _)| span)
+                .collect(),
+            Matcher::Regex(regex) => regex
+                .find_iter(text)
+                .filter(|found| !found.is_empty())
+                .map(|found| found.range())
+                .collect(),
+            Matcher::Fuzzy(words) => words
+                .iter()
+                .filter_map(|word| fuzzy_match(word, text))
+                .flat_map(|(_, ranges)| ranges)
+                .collect(),
+        };
+        merge(ranges)
+    }
+
+    /// Score of a message for the regex and fuzzy modes and the scopes it
+    /// matched in, `None` if nothing in `scopes` matches.
+    pub fn score(&self, text: &MessageText, scopes: Scopes) -> Option<(f32, Scopes)> {
+        let sections: Vec<(Scope, &str)> = text.sections().filter(|(scope, _)| scopes.contains(*scope)).collect();
+        let mut found_in = Scopes::NONE;
+        match self {
+            Matcher::Terms(_) => None,
+            Matcher::Regex(regex) => {
+                let mut count = 0;
+                for &(scope, content) in &sections {
+                    let found = regex.find_iter(content).filter(|found| !found.is_empty()).count();
+                    if found > 0 {
+                        count += found;
+                        found_in.insert(scope);
+                    }
+                }
+                (count > 0).then_some((count as f32, found_in))
+            }
+            // Every word has to match somewhere in the message
+            Matcher::Fuzzy(words) => {
+                let total = words.iter().try_fold(0.0, |total, word| {
+                    let (best, scope) = sections
+                        .iter()
+                        .filter_map(|&(scope, content)| fuzzy_match(word, content).map(|(score, _)| (score, scope)))
+                        .reduce(|a, b| if b.0 > a.0 { b } else { a })?;
+                    found_in.insert(scope);
+                    Some(total + best)
+                })?;
+                Some((total, found_in))
+            }
+        }
+    }
+
+    /// Scans every message of `projects` within `scopes`, best project
+    /// first. Used by the regex and fuzzy modes.
+    pub fn search(&self, projects: &[Arc<ProjectFile>], scopes: Scopes) -> Vec<SearchResult> {
+        let mut results: Vec<SearchResult> = projects
+            .iter()
+            .filter_map(|project| {
+                let hits: Vec<(usize, f32, Scopes)> = project
+                    .texts
+                    .iter()
+                    .enumerate()
+                    .filter_map(|(i, text)| self.score(text, scopes).map(|(score, found_in)| (i, score, found_in)))
+                    .collect();
+                (!hits.is_empty()).then(|| SearchResult {
+                    project: Arc::clone(project),
+                    message_indices: hits.iter().map(|&(i, _, _)| i).collect(),
+                    scores: hits.iter().map(|&(_, score, _)| score).collect(),
+                    scopes: hits.iter().map(|&(_, _, found_in)| found_in).collect(),
+                })
+            })
+            .collect();
+
+        results.sort_by(|a, b| {
+            b.score()
+                .partial_cmp(&a.score())
+                .unwrap_or(std::cmp::Ordering::Equal)
+                .then_with(|| b.project.modified.cmp(&a.project.modified))
+        });
+        results
+    }
+}
+
+/// Whether a regex spells out an uppercase character. Escapes such as `\S`
+/// or `\p{Lu}` are not literal text and don't count.
+fn has_uppercase_literal(pattern: &str) -> bool {
+    let mut chars = pattern.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => {
+                // `\pL` names its class with one letter, `\p{Lu}` with several
+                if let Some('p' | 'P') = chars.next()
+                    && chars.next() == Some('{')
+                {
+                    chars.by_ref().take_while(|&c| c != '}').for_each(drop);
+                }
+            }
+            c if c.is_uppercase() => return true,
+            _ => {}
+        }
+    }
+    false
+}
+
+/// Characters a fuzzy match may skip between two pattern characters.
+const MAX_GAP: usize = 8;
+
+/// Matches `pattern` (lowercase) as a subsequence of one line of `text`,
+/// allowing one typo per four pattern characters. Returns the best score and
+/// the byte ranges of the matched characters.
+pub fn fuzzy_match(pattern: &[char], text: &str) -> Option<(f32, Vec<Range<usize>>)> {
+    if pattern.is_empty() {
+        return None;
+    }
+    let allowed_typos = pattern.len() / 4;
+    let mut best: Option<(f32, Vec<Range<usize>>)> = None;
+
+    let mut line_start = 0;
+    for line in text.split('\n') {
+        // (byte offset in `text`, lowercased char, byte length)
+        let chars: Vec<(usize, char, usize)> = line
+            .char_indices()
+            .map(|(i, c)| (line_start + i, c.to_lowercase().next().unwrap_or(c), c.len_utf8()))
+            .collect();
+        line_start += line.len() + 1;
+
+        for start in 0..chars.len() {
+            // A match may only begin on one of the first (typo-skippable) pattern chars
+            if !pattern[..=allowed_typos.min(pattern.len() - 1)].contains(&chars[start].1) {
+                continue;
+            }
+            if let Some((score, positions)) = match_from(pattern, &chars, start, allowed_typos)
+                && best.as_ref().is_none_or(|(best, _)| score > *best)
+            {
+                let ranges = positions
+                    .iter()
+                    .map(|&at| {
+                        let (i, _, len) = chars[at];
+                        i..i + len
+                    })
+                    .collect();
+                best = Some((score, merge(ranges)));
+            }
+        }
+    }
+    best
+}
+
+/// Greedily matches `pattern` against `chars` starting at `start`.
+fn match_from(
+    pattern: &[char],
+    chars: &[(usize, char, usize)],
+    start: usize,
+    allowed_typos: usize,
+) -> Option<(f32, Vec<usize>)> {
+    let mut positions: Vec<usize> = Vec::with_capacity(pattern.len());
+    let mut typos = 0;
+    let mut cursor = start;
+    let mut score = 0.0;
+
+    for &wanted in pattern {
+        let window = cursor..(cursor + MAX_GAP + 1).min(chars.len());
+        match window.clone().find(|&at| chars[at].1 == wanted) {
+            Some(at) => {
+                score += 16.0;
+                match positions.last() {
+                    Some(&previous) if previous + 1 == at => score += 8.0,
+                    Some(&previous) => score -= (at - previous - 1) as f32,
+                    None => {}
+                }
+                if at == 0 || !chars[at - 1].1.is_alphanumeric() {
+                    score += 8.0;
+                }
+                positions.push(at);
+                cursor = at + 1;
+            }
+            None => {
+                typos += 1;
+                if typos > allowed_typos {
+                    return None;
+                }
+                score -= 12.0;
+            }
+        }
+    }
+    (!positions.is_empty()).then_some((score, positions))
+}
+
+/// Sorts `ranges` and joins the ones that touch or overlap.
+fn merge(mut ranges: Vec<Range<usize>>) -> Vec<Range<usize>> {
+    ranges.sort_by_key(|range| range.start);
+    let mut merged: Vec<Range<usize>> = Vec::with_capacity(ranges.len());
+    for range in ranges {
+        match merged.last_mut() {
+            Some(last) if range.start <= last.end => last.end = last.end.max(range.end),
+            _ => merged.push(range),
+        }
+    }
+    merged
+}
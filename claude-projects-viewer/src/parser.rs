@@ -1,92 +1,240 @@
-use crate::types::{ProjectFile, SessionMessage};
+use crate::cache::IndexCache;
+use crate::config::LoadOptions;
+use crate::types::{MessageText, ParseDiagnostic, ProjectFile, SessionMessage};
 use glob::glob;
-use home::home_dir;
+use std::collections::HashSet;
 use std::fs::File;
 use std::io::{BufRead, BufReader};
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime};
+
+/// A session file found on disk, before it is parsed.
+#[derive(Debug, Clone)]
+pub struct ProjectEntry {
+    pub path: PathBuf,
+    pub size: u64,
+    pub modified: Option<SystemTime>,
+}
+
+/// Aggregate figures for one parsing run.
+#[derive(Debug, Clone, Copy)]
+pub struct LoadStats {
+    pub files: usize,
+    pub bytes: u64,
+    /// Files restored from the index cache instead of being parsed.
+    pub cached: usize,
+    pub threads: usize,
+    pub elapsed: Duration,
+}
+
+impl LoadStats {
+    /// One-line summary such as
+    /// `Parsed 30 files (12.3 MB, 25 from cache) in 85 ms on 8 threads`.
+    pub fn describe(&self) -> String {
+        format!(
+            "Parsed {} files ({:.1} MB, {} from cache) in {} ms on {} thread{}",
+            self.files,
+            self.bytes as f64 / 1_000_000.0,
+            self.cached,
+            self.elapsed.as_millis(),
+            self.threads,
+            if self.threads == 1 { "" } else { "s" },
+        )
+    }
+}
 
 pub struct ProjectParser;
 
 impl ProjectParser {
-    pub fn load_all_projects() -> Result<Vec<ProjectFile>, Box<dyn std::error::Error>> {
-        let home = home_dir().ok_or("Could not find home directory")?;
-        let pattern = format!("{}/.claude/projects/**/*.jsonl", home.display());
+    pub fn load_all_projects(options: &LoadOptions) -> Result<Vec<ProjectFile>, Box<dyn std::error::Error>> {
+        let entries = Self::discover_project_files(&options.roots)?;
+        let window = match options.limit {
+            Some(limit) => &entries[..limit.min(entries.len())],
+            None => &entries[..],
+        };
         
-        let mut project_paths: Vec<_> = glob(&pattern)?
-            .flatten()
-            .filter_map(|path| {
-                std::fs::metadata(&path).ok().map(|metadata| {
-                    let modified = metadata.modified().ok();
-                    (path, modified)
-                })
-            })
-            .collect();
+        Ok(Self::load_projects(window, options.threads, options.cache().as_ref()))
+    }
+    
+    /// Lists every `*.jsonl` file under the given roots, newest first.
+    pub fn discover_project_files(roots: &[PathBuf]) -> Result<Vec<ProjectEntry>, Box<dyn std::error::Error>> {
+        let mut entries = Vec::new();
+        // A root inside another root must not list its sessions twice
+        let mut seen = HashSet::new();
+        
+        for root in roots {
+            let pattern = format!("{}/**/*.jsonl", glob::Pattern::escape(&root.to_string_lossy()));
+            for path in glob(&pattern)?.flatten() {
+                if !seen.insert(std::fs::canonicalize(&path).unwrap_or_else(|_| path.clone())) {
+                    continue;
+                }
+                if let Ok(metadata) = std::fs::metadata(&path) {
+                    entries.push(ProjectEntry {
+                        path,
+                        size: metadata.len(),
+                        modified: metadata.modified().ok(),
+                    });
+                }
+            }
+        }
         
         // 最終更新日時でソート（新しい順）
-        project_paths.sort_by(|a, b| b.1.cmp(&a.1));
+        entries.sort_by_key(|entry| std::cmp::Reverse(entry.modified));
+        
+        Ok(entries)
+    }
+    
+    /// Parses `entries` on up to `threads` worker threads. The output keeps
+    /// the order of `entries`; files that cannot be opened are skipped.
+    pub fn load_projects(entries: &[ProjectEntry], threads: usize, cache: Option<&IndexCache>) -> Vec<ProjectFile> {
+        Self::load_projects_with_stats(entries, threads, cache).0
+    }
+    
+    pub fn load_projects_with_stats(
+        entries: &[ProjectEntry],
+        threads: usize,
+        cache: Option<&IndexCache>,
+    ) -> (Vec<ProjectFile>, LoadStats) {
+        let started = Instant::now();
+        let threads = threads.clamp(1, entries.len().max(1));
+        let next = AtomicUsize::new(0);
+        let mut slots: Vec<Option<ProjectFile>> = entries.iter().map(|_| None).collect();
         
-        // 最新30個のみ読み込み
-        let mut projects = Vec::new();
-        for (path, _) in project_paths.into_iter().take(30) {
-            if let Ok(project) = Self::load_project_file(&path) {
-                projects.push(project);
+        thread::scope(|scope| {
+            let workers: Vec<_> = (0..threads)
+                .map(|_| {
+                    scope.spawn(|| {
+                        let mut parsed = Vec::new();
+                        loop {
+                            let i = next.fetch_add(1, Ordering::Relaxed);
+                            let Some(entry) = entries.get(i) else { break };
+                            if let Ok(project) = Self::load_entry(entry, cache) {
+                                parsed.push((i, project));
+                            }
+                        }
+                        parsed
+                    })
+                })
+                .collect();
+            
+            for worker in workers {
+                for (i, project) in worker.join().expect("parser thread panicked") {
+                    slots[i] = Some(project);
+                }
             }
+        });
+        
+        let mut projects: Vec<ProjectFile> = slots.into_iter().flatten().collect();
+        ProjectFile::resolve_titles(&mut projects);
+        let stats = LoadStats {
+            files: projects.len(),
+            bytes: projects.iter().map(|project| project.size).sum(),
+            cached: projects.iter().filter(|project| !project.is_loaded()).count(),
+            threads,
+            elapsed: started.elapsed(),
+        };
+        if let Some(cache) = cache {
+            // 古いエントリや消えたセッションのエントリを掃除する (失敗しても読み込みには影響しない)
+            let _ = cache.prune();
+        }
+        (projects, stats)
+    }
+    
+    /// Restores `entry` from the index cache when it is unchanged, otherwise
+    /// parses it and refreshes the cache.
+    pub fn load_entry(entry: &ProjectEntry, cache: Option<&IndexCache>) -> Result<ProjectFile, Box<dyn std::error::Error>> {
+        if let Some(project) = cache.and_then(|cache| cache.get(entry)) {
+            return Ok(project);
         }
         
-        Ok(projects)
+        let project = Self::load_project_file(&entry.path)?;
+        if let Some(cache) = cache {
+            // The cache is only an optimisation; a failed write just means a reparse next time
+            let _ = cache.put(&project);
+        }
+        Ok(project)
     }
     
     pub fn load_project_file(path: &Path) -> Result<ProjectFile, Box<dyn std::error::Error>> {
         let mut project = ProjectFile::new(path.to_string_lossy().to_string());
         
         let file = File::open(path)?;
-        let reader = BufReader::new(file);
+        if let Ok(metadata) = file.metadata() {
+            project.size = metadata.len();
+            project.modified = metadata.modified().ok();
+        }
+        let mut reader = BufReader::new(file);
+        let mut buf = Vec::new();
+        let mut line_number = 0;
+        let mut byte_offset = 0u64;
         
-        for line in reader.lines() {
-            let line = line?;
+        loop {
+            buf.clear();
+            let read = reader.read_until(b'\n', &mut buf)?;
+            if read == 0 {
+                break;
+            }
+            line_number += 1;
+            let line_offset = byte_offset;
+            byte_offset += read as u64;
+            
+            let line = match std::str::from_utf8(&buf) {
+                Ok(line) => line,
+                Err(e) => {
+                    project.diagnostics.push(ParseDiagnostic {
+                        path: project.path.clone(),
+                        line: line_number,
+                        byte_offset: line_offset,
+                        error: e.to_string(),
+                        record_type: None,
+                    });
+                    continue;
+                }
+            };
             if line.trim().is_empty() {
                 continue;
             }
             
-            match serde_json::from_str::<SessionMessage>(&line) {
+            match serde_json::from_str::<SessionMessage>(line) {
                 Ok(message) => project.messages.push(message),
-                Err(_) => {
-                    // Skip parsing errors silently to avoid cluttering output
-                    // Continue parsing other lines
+                Err(e) => {
+                    // Keep going, but remember why the line was dropped
+                    project.diagnostics.push(ParseDiagnostic {
+                        path: project.path.clone(),
+                        line: line_number,
+                        byte_offset: line_offset,
+                        error: e.to_string(),
+                        record_type: Self::record_type(line),
+                    });
                 }
             }
         }
         
-        Ok(project)
-    }
-    
-    pub fn search_messages(
-        projects: &[ProjectFile],
-        query: &str,
-    ) -> Vec<(String, Vec<SessionMessage>)> {
-        let query_lower = query.to_lowercase();
-        let mut results = Vec::new();
-        
-        
-        for project in projects {
-            let mut matching_messages = Vec::new();
-            
-            for message in &project.messages {
-                let text_contents = message.get_text_content();
-                let matches = text_contents
-                    .iter()
-                    .any(|content| content.to_lowercase().contains(&query_lower));
-                
-                if matches {
-                    matching_messages.push(message.clone());
-                }
-            }
-            
-            if !matching_messages.is_empty() {
-                results.push((project.project_name.clone(), matching_messages));
+        project.texts = project.messages.iter().map(MessageText::from_message).collect();
+        let cwd = project.messages.iter().find_map(SessionMessage::get_cwd).map(str::to_string);
+        project.resolve_path(cwd.as_deref());
+        project.resolve_title();
+        // 1つのAPIレスポンスは content ブロックごとに別レコードになり、同じ usage が繰り返されるので最初の1つだけ数える
+        let mut responses = HashSet::new();
+        for (message, text) in project.messages.iter().zip(project.texts.iter_mut()) {
+            if let Some(id) = message.get_response_id()
+                && !responses.insert(id)
+            {
+                text.tokens = None;
             }
         }
         
-        results
+        Ok(project)
+    }
+    
+    /// Extracts the `type` field of a line that failed to parse, if it is JSON at all.
+    fn record_type(line: &str) -> Option<String> {
+        serde_json::from_str::<serde_json::Value>(line)
+            .ok()?
+            .get("type")?
+            .as_str()
+            .map(str::to_string)
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,90 @@
+use home::home_dir;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Recovers the directory a project folder under `~/.claude/projects` was
+/// named after.
+///
+/// The folder name is the path with every `/`, `.` and other punctuation
+/// replaced by `-`, so
+/// `-Users-foo-src-github-com-org-repo` may stand for
+/// `/Users/foo/src/github.com/org/repo` or `/Users/foo/src-github/com/org/repo`.
+/// Directories that exist on this machine decide; when none do, each `-` is
+/// read as `/`.
+pub fn demangle(name: &str) -> String {
+    let parts: Vec<&str> = name.trim_start_matches('-').split('-').collect();
+    match find_existing(Path::new("/"), &parts) {
+        Some(path) => path.to_string_lossy().to_string(),
+        None => format!("/{}", parts.join("/")),
+    }
+}
+
+/// Finds an existing path below `base` whose components, mangled, spell
+/// `parts`. Longer directory names are tried first.
+fn find_existing(base: &Path, parts: &[&str]) -> Option<PathBuf> {
+    if parts.is_empty() {
+        return Some(base.to_path_buf());
+    }
+    let mut candidates: Vec<(usize, PathBuf)> = fs::read_dir(base)
+        .ok()?
+        .flatten()
+        .filter_map(|entry| {
+            let mangled = mangle(&entry.file_name().to_string_lossy());
+            let len = mangled.split('-').count();
+            (len <= parts.len() && parts[..len].join("-") == mangled).then(|| (len, entry.path()))
+        })
+        .collect();
+    candidates.sort_by_key(|(len, _)| std::cmp::Reverse(*len));
+    candidates
+        .into_iter()
+        .filter(|(len, path)| *len == parts.len() || path.is_dir())
+        .find_map(|(len, path)| find_existing(&path, &parts[len..]))
+}
+
+/// How Claude Code names a project folder: every character other than an
+/// ASCII letter or digit becomes `-`.
+fn mangle(name: &str) -> String {
+    name.chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+        .collect()
+}
+
+/// Name to show for a project: the repository it is in, followed by the
+/// path inside the repository for subdirectories; the last component of the
+/// path outside of repositories.
+pub fn short_name(path: &str) -> String {
+    let path = Path::new(path);
+    let name = |path: &Path| {
+        path.file_name()
+            .map_or_else(|| path.to_string_lossy().to_string(), |name| name.to_string_lossy().to_string())
+    };
+    match git_root(path) {
+        Some(root) => match path.strip_prefix(&root) {
+            Ok(inner) if !inner.as_os_str().is_empty() => format!("{}/{}", name(&root), inner.display()),
+            _ => name(&root),
+        },
+        None => name(path),
+    }
+}
+
+/// The closest directory at or above `path` containing `.git`, not counting
+/// the home directory (which may hold a dotfiles repository).
+fn git_root(path: &Path) -> Option<PathBuf> {
+    let home = home_dir();
+    path.ancestors()
+        .take_while(|dir| Some(*dir) != home.as_deref())
+        .find(|dir| dir.join(".git").exists())
+        .map(Path::to_path_buf)
+}
+
+/// `path` with the home directory shown as `~`.
+pub fn display_path(path: &str) -> String {
+    match home_dir() {
+        Some(home) => match Path::new(path).strip_prefix(&home) {
+            Ok(rest) if rest.as_os_str().is_empty() => "~".to_string(),
+            Ok(rest) => format!("~/{}", rest.display()),
+            Err(_) => path.to_string(),
+        },
+        None => path.to_string(),
+    }
+}
@@ -0,0 +1,134 @@
+use crate::types::{MessageText, ProjectFile, TokenUsage};
+use home::home_dir;
+use serde::{Deserialize, Serialize};
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Price of one model in US dollars per million tokens.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ModelPrice {
+    /// A model id, or part of one: `sonnet` prices every model whose id
+    /// contains `sonnet`. The longest matching entry wins.
+    pub model: String,
+    pub input: f64,
+    pub output: f64,
+    /// Tokens written to the prompt cache.
+    pub cache_write: f64,
+    /// Tokens read from the prompt cache.
+    pub cache_read: f64,
+}
+
+/// Spend above which sessions and days are highlighted, in US dollars.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct Budget {
+    #[serde(default)]
+    pub session: Option<f64>,
+    #[serde(default)]
+    pub day: Option<f64>,
+}
+
+/// Model prices used to estimate what sessions cost, read from a JSON file:
+///
+/// ```json
+/// {
+///   "models": [
+///     { "model": "opus", "input": 15, "output": 75, "cache_write": 18.75, "cache_read": 1.5 }
+///   ],
+///   "budget": { "session": 5, "day": 20 }
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PricingTable {
+    pub models: Vec<ModelPrice>,
+    #[serde(default)]
+    pub budget: Budget,
+}
+
+impl Default for PricingTable {
+    /// List prices of the Claude model families, without budgets.
+    fn default() -> Self {
+        let price = |model: &str, input: f64, output: f64| ModelPrice {
+            model: model.to_string(),
+            input,
+            output,
+            cache_write: input * 1.25,
+            cache_read: input * 0.1,
+        };
+        Self {
+            models: vec![
+                price("opus", 15.0, 75.0),
+                price("opus-4-5", 5.0, 25.0),
+                price("sonnet", 3.0, 15.0),
+                price("haiku", 0.8, 4.0),
+                price("haiku-4-5", 1.0, 5.0),
+                price("3-haiku", 0.25, 1.25),
+            ],
+            budget: Budget::default(),
+        }
+    }
+}
+
+impl PricingTable {
+    /// `$XDG_CONFIG_HOME/claude-projects-viewer/pricing.json`, falling back
+    /// to `~/.config/claude-projects-viewer/pricing.json`.
+    pub fn default_path() -> Option<PathBuf> {
+        let base = match env::var_os("XDG_CONFIG_HOME") {
+            Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+            _ => home_dir()?.join(".config"),
+        };
+        Some(base.join("claude-projects-viewer").join("pricing.json"))
+    }
+
+    /// Reads the table from `path`, or from [`PricingTable::default_path`]
+    /// if it exists, or uses the built-in prices.
+    pub fn load(path: Option<&Path>) -> Result<Self, String> {
+        let path = match path {
+            Some(path) => path.to_path_buf(),
+            None => match Self::default_path().filter(|path| path.exists()) {
+                Some(path) => path,
+                None => return Ok(Self::default()),
+            },
+        };
+        let data = fs::read(&path).map_err(|e| format!("cannot read {}: {e}", path.display()))?;
+        serde_json::from_slice(&data).map_err(|e| format!("invalid pricing table {}: {e}", path.display()))
+    }
+
+    /// The entry for `model`: an exact match, else the longest entry the id
+    /// contains.
+    pub fn price(&self, model: &str) -> Option<&ModelPrice> {
+        self.models.iter().find(|price| price.model == model).or_else(|| {
+            self.models
+                .iter()
+                .filter(|price| model.contains(price.model.as_str()))
+                .max_by_key(|price| price.model.len())
+        })
+    }
+
+    /// Estimated cost of `tokens` used by `model`, `None` for models the
+    /// table does not list.
+    pub fn cost(&self, model: Option<&str>, tokens: &TokenUsage) -> Option<f64> {
+        let price = self.price(model?)?;
+        let cost = tokens.input as f64 * price.input
+            + tokens.output as f64 * price.output
+            + tokens.cache_creation as f64 * price.cache_write
+            + tokens.cache_read as f64 * price.cache_read;
+        Some(cost / 1_000_000.0)
+    }
+
+    /// Estimated cost of one message, `None` if it carries no usage or its
+    /// model is not priced.
+    pub fn message_cost(&self, text: &MessageText) -> Option<f64> {
+        self.cost(text.model.as_deref(), text.tokens.as_ref()?)
+    }
+
+    /// Estimated cost of every priced response in a session.
+    pub fn session_cost(&self, project: &ProjectFile) -> f64 {
+        project.texts.iter().filter_map(|text| self.message_cost(text)).fold(0.0, |sum, cost| sum + cost)
+    }
+
+    /// Whether `cost` is over the session budget.
+    pub fn session_over_budget(&self, cost: f64) -> bool {
+        self.budget.session.is_some_and(|limit| cost > limit)
+    }
+}
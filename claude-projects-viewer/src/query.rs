@@ -0,0 +1,357 @@
+use crate::types::{MessageText, ProjectFile};
+use chrono::{DateTime, NaiveDate, Utc};
+use std::fmt;
+
+/// A metadata field a query can filter on, written `name:value`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Field {
+    /// Record type: `user`, `assistant`, `summary`, `system`, ...
+    Type,
+    /// Name of a tool the message calls.
+    Tool,
+    /// Model that wrote the message; matches any part of the model id.
+    Model,
+    /// Git branch the session was on.
+    Branch,
+    /// Any part of the project's short name or folder name.
+    Project,
+    /// Any part of the project's directory.
+    Path,
+    /// Prefix of the session id.
+    Session,
+    /// Messages on or after a date.
+    After,
+    /// Messages before a date.
+    Before,
+}
+
+impl Field {
+    fn from_name(name: &str) -> Option<Self> {
+        Some(match name.to_lowercase().as_str() {
+            "type" => Field::Type,
+            "tool" => Field::Tool,
+            "model" => Field::Model,
+            "branch" => Field::Branch,
+            "project" => Field::Project,
+            "path" => Field::Path,
+            "session" => Field::Session,
+            "after" => Field::After,
+            "before" => Field::Before,
+            _ => return None,
+        })
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Field::Type => "type",
+            Field::Tool => "tool",
+            Field::Model => "model",
+            Field::Branch => "branch",
+            Field::Project => "project",
+            Field::Path => "path",
+            Field::Session => "session",
+            Field::After => "after",
+            Field::Before => "before",
+        }
+    }
+}
+
+/// A parsed search query.
+///
+/// Whitespace-separated parts must all match, `OR` (or `|`) separates
+/// alternatives, `-` negates a part, `"..."` matches a phrase and
+/// parentheses group. `field:value` filters on message metadata, see
+/// [`Field`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum Query {
+    /// A word, matched against the message text by prefix.
+    Term(String),
+    /// Words that must appear next to each other, in order.
+    Phrase(String),
+    Filter(Filter),
+    Not(Box<Query>),
+    /// Every part must match. Empty matches everything.
+    And(Vec<Query>),
+    /// At least one part must match.
+    Or(Vec<Query>),
+}
+
+/// A `field:value` condition.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Filter {
+    Text(Field, String),
+    After(DateTime<Utc>),
+    Before(DateTime<Utc>),
+}
+
+impl Query {
+    pub fn parse(input: &str) -> Result<Self, String> {
+        let tokens = lex(input);
+        let mut parser = Parser { tokens, pos: 0 };
+        let query = parser.parse_or()?;
+        match parser.tokens.get(parser.pos) {
+            None => Ok(query),
+            Some(token) => Err(format!("unexpected {token}")),
+        }
+    }
+
+    /// Whether the query matches everything, e.g. an empty one.
+    pub fn is_empty(&self) -> bool {
+        matches!(self, Query::And(parts) if parts.iter().all(Query::is_empty))
+    }
+
+    /// Text searched for outside of negations, used to rank matches.
+    pub fn positive_text(&self) -> Vec<&str> {
+        let mut texts = Vec::new();
+        self.collect_positive_text(&mut texts);
+        texts
+    }
+
+    fn collect_positive_text<'a>(&'a self, texts: &mut Vec<&'a str>) {
+        match self {
+            Query::Term(text) | Query::Phrase(text) => texts.push(text),
+            Query::And(parts) | Query::Or(parts) => {
+                for part in parts {
+                    part.collect_positive_text(texts);
+                }
+            }
+            Query::Filter(_) | Query::Not(_) => {}
+        }
+    }
+}
+
+impl Filter {
+    fn parse(field: Field, value: &str) -> Result<Self, String> {
+        if value.is_empty() {
+            return Err(format!("{}: needs a value", field.name()));
+        }
+        match field {
+            Field::After => parse_date(value).map(Filter::After),
+            Field::Before => parse_date(value).map(Filter::Before),
+            _ => Ok(Filter::Text(field, value.to_lowercase())),
+        }
+    }
+
+    /// Evaluates the filter against one message of `project`.
+    pub fn matches(&self, project: &ProjectFile, text: &MessageText) -> bool {
+        match self {
+            Filter::Text(field, value) => {
+                let equals = |s: &Option<String>| s.as_deref().is_some_and(|s| s.eq_ignore_ascii_case(value));
+                match field {
+                    Field::Type => text.kind.eq_ignore_ascii_case(value),
+                    Field::Tool => text.tools.iter().any(|tool| tool.eq_ignore_ascii_case(value)),
+                    Field::Model => text
+                        .model
+                        .as_deref()
+                        .is_some_and(|model| model.to_lowercase().contains(value.as_str())),
+                    Field::Branch => equals(&text.git_branch),
+                    Field::Project => {
+                        project.short_name.to_lowercase().contains(value.as_str())
+                            || project.project_name.to_lowercase().contains(value.as_str())
+                    }
+                    Field::Path => project.project_path.to_lowercase().contains(value.as_str()),
+                    Field::Session => text
+                        .session_id
+                        .as_deref()
+                        .is_some_and(|id| id.to_lowercase().starts_with(value.as_str())),
+                    Field::After | Field::Before => false,
+                }
+            }
+            Filter::After(date) => text.time.is_some_and(|time| time >= *date),
+            Filter::Before(date) => text.time.is_some_and(|time| time < *date),
+        }
+    }
+}
+
+/// Accepts `YYYY-MM-DD` (midnight UTC) or a full RFC 3339 timestamp.
+fn parse_date(value: &str) -> Result<DateTime<Utc>, String> {
+    if let Ok(date) = NaiveDate::parse_from_str(value, "%Y-%m-%d") {
+        return Ok(date.and_hms_opt(0, 0, 0).unwrap_or_default().and_utc());
+    }
+    DateTime::parse_from_rfc3339(value)
+        .map(|time| time.with_timezone(&Utc))
+        .map_err(|_| format!("invalid date: {value} (expected YYYY-MM-DD)"))
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Open,
+    Close,
+    Or,
+    Not,
+    Word(String),
+    Quoted(String),
+    Field(String, String),
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Token::Open => write!(f, "'('"),
+            Token::Close => write!(f, "')'"),
+            Token::Or => write!(f, "OR"),
+            Token::Not => write!(f, "'-'"),
+            Token::Word(word) => write!(f, "'{word}'"),
+            Token::Quoted(text) => write!(f, "\"{text}\""),
+            Token::Field(name, value) => write!(f, "'{name}:{value}'"),
+        }
+    }
+}
+
+fn lex(input: &str) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    let mut chars = input.chars().peekable();
+
+    while let Some(&c) = chars.peek() {
+        match c {
+            c if c.is_whitespace() => {
+                chars.next();
+            }
+            '(' => {
+                chars.next();
+                tokens.push(Token::Open);
+            }
+            ')' => {
+                chars.next();
+                tokens.push(Token::Close);
+            }
+            '|' => {
+                chars.next();
+                tokens.push(Token::Or);
+            }
+            // A dash only negates at the start of a part
+            '-' => {
+                chars.next();
+                tokens.push(Token::Not);
+            }
+            '"' => {
+                chars.next();
+                // An unclosed quote runs to the end, which is handy while typing
+                let text: String = chars.by_ref().take_while(|&c| c != '"').collect();
+                tokens.push(Token::Quoted(text));
+            }
+            _ => {
+                let mut word = String::new();
+                while let Some(&c) = chars.peek() {
+                    if c.is_whitespace() || c == '(' || c == ')' || c == '"' {
+                        break;
+                    }
+                    word.push(c);
+                    chars.next();
+                }
+
+                if word == "OR" {
+                    tokens.push(Token::Or);
+                } else if let Some((name, value)) = word.split_once(':')
+                    && Field::from_name(name).is_some()
+                {
+                    // `field:"quoted value"`
+                    let value = if value.is_empty() && chars.peek() == Some(&'"') {
+                        chars.next();
+                        chars.by_ref().take_while(|&c| c != '"').collect()
+                    } else {
+                        value.to_string()
+                    };
+                    tokens.push(Token::Field(name.to_string(), value));
+                } else {
+                    tokens.push(Token::Word(word));
+                }
+            }
+        }
+    }
+    tokens
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    // or := and ("OR" and)*
+    fn parse_or(&mut self) -> Result<Query, String> {
+        let mut alternatives = vec![self.parse_and()?];
+        while self.peek() == Some(&Token::Or) {
+            // An empty alternative would match everything
+            if alternatives.len() == 1 && is_nothing(&alternatives[0]) {
+                return Err("'OR' must follow a term".to_string());
+            }
+            self.pos += 1;
+            let alternative = self.parse_and()?;
+            if is_nothing(&alternative) {
+                return Err("'OR' must be followed by a term".to_string());
+            }
+            alternatives.push(alternative);
+        }
+        Ok(if alternatives.len() == 1 {
+            alternatives.remove(0)
+        } else {
+            Query::Or(alternatives)
+        })
+    }
+
+    // and := unary*
+    fn parse_and(&mut self) -> Result<Query, String> {
+        let mut parts = Vec::new();
+        while let Some(token) = self.peek() {
+            if matches!(token, Token::Or | Token::Close) {
+                break;
+            }
+            parts.push(self.parse_unary()?);
+        }
+        Ok(if parts.len() == 1 {
+            parts.remove(0)
+        } else {
+            Query::And(parts)
+        })
+    }
+
+    // unary := "-" unary | atom
+    fn parse_unary(&mut self) -> Result<Query, String> {
+        if self.peek() == Some(&Token::Not) {
+            self.pos += 1;
+            return match self.peek() {
+                None | Some(Token::Or | Token::Close) => Err("'-' must be followed by a term".to_string()),
+                _ => Ok(Query::Not(Box::new(self.parse_unary()?))),
+            };
+        }
+        self.parse_atom()
+    }
+
+    // atom := "(" or ")" | field ":" value | '"' phrase '"' | word
+    fn parse_atom(&mut self) -> Result<Query, String> {
+        match self.next() {
+            Some(Token::Open) => {
+                let query = self.parse_or()?;
+                match self.next() {
+                    Some(Token::Close) if is_nothing(&query) => Err("empty group".to_string()),
+                    Some(Token::Close) => Ok(query),
+                    _ => Err("missing ')'".to_string()),
+                }
+            }
+            Some(Token::Field(name, value)) => {
+                let field = Field::from_name(&name).ok_or_else(|| format!("unknown field: {name}"))?;
+                Filter::parse(field, &value).map(Query::Filter)
+            }
+            Some(Token::Quoted(text)) => Ok(Query::Phrase(text)),
+            Some(Token::Word(word)) => Ok(Query::Term(word)),
+            Some(token) => Err(format!("unexpected {token}")),
+            None => Err("unexpected end of query".to_string()),
+        }
+    }
+}
+
+/// Whether `query` is what `parse_and` returns when there is no term at all.
+fn is_nothing(query: &Query) -> bool {
+    matches!(query, Query::And(parts) if parts.is_empty())
+}
@@ -0,0 +1,189 @@
+use crate::matcher::Matcher;
+use crate::types::{MessageText, Scope, Scopes, SearchResult};
+use std::ops::Range;
+
+/// A piece of a longer text shown in place of the whole, with the matches
+/// it contains.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Snippet {
+    /// The excerpt, with `...` where it was cut and runs of whitespace
+    /// (including newlines) collapsed to one space.
+    pub text: String,
+    /// Byte ranges of `text` that matched, sorted and not overlapping.
+    pub highlights: Vec<Range<usize>>,
+}
+
+impl Snippet {
+    /// The first `width` characters of `text`, for when nothing matched.
+    pub fn leading(text: &str, width: usize) -> Self {
+        let chars = text.chars().count();
+        cut(text, 0..width.min(chars), &[])
+    }
+
+    /// Joins snippets into one line. Their cut ends already show `...`.
+    pub fn join(snippets: &[Snippet]) -> Self {
+        let mut joined = Snippet::default();
+        for snippet in snippets {
+            if !joined.text.is_empty() {
+                joined.text.push(' ');
+            }
+            let offset = joined.text.len();
+            joined.text.push_str(&snippet.text);
+            joined
+                .highlights
+                .extend(snippet.highlights.iter().map(|range| range.start + offset..range.end + offset));
+        }
+        joined
+    }
+}
+
+/// Cuts `context` characters on each side of every match in `text`. Matches
+/// whose context overlaps share one snippet. `matches` are byte ranges,
+/// sorted and not overlapping, as returned by
+/// [`Matcher::find`](crate::matcher::Matcher::find).
+pub fn extract(text: &str, matches: &[Range<usize>], context: usize) -> Vec<Snippet> {
+    let offsets: Vec<usize> = text.char_indices().map(|(i, _)| i).collect();
+    let char_at = |byte: usize| offsets.partition_point(|&i| i < byte);
+
+    // Character windows around each match, merged when they touch
+    let mut windows: Vec<(Range<usize>, Vec<Range<usize>>)> = Vec::new();
+    for range in matches {
+        let (start, end) = (char_at(range.start), char_at(range.end));
+        let window = start.saturating_sub(context)..(end + context).min(offsets.len());
+        match windows.last_mut() {
+            Some((last, ranges)) if window.start <= last.end => {
+                last.end = last.end.max(window.end);
+                ranges.push(range.clone());
+            }
+            _ => windows.push((window, vec![range.clone()])),
+        }
+    }
+
+    windows
+        .into_iter()
+        .map(|(window, ranges)| cut(text, window, &ranges))
+        .collect()
+}
+
+/// Builds the snippet for the characters `window` of `text`, moving
+/// `matches` (byte ranges of `text`) into it.
+fn cut(text: &str, window: Range<usize>, matches: &[Range<usize>]) -> Snippet {
+    let mut out = String::new();
+    if window.start > 0 {
+        out.push_str("...");
+    }
+
+    // Byte offset in `text` -> byte offset in `out`, for every char boundary in the window
+    let mut mapped: Vec<(usize, usize)> = Vec::new();
+    let mut in_space = false;
+    for (i, c) in text.char_indices().skip(window.start).take(window.len()) {
+        if c.is_whitespace() {
+            in_space = true;
+        } else if in_space {
+            // A run of whitespace becomes one space once more text follows,
+            // so whitespace before a cut is dropped
+            out.push(' ');
+            in_space = false;
+        }
+        mapped.push((i, out.len()));
+        if !in_space {
+            out.push(c);
+        }
+    }
+    let end_byte = text
+        .char_indices()
+        .nth(window.end)
+        .map_or(text.len(), |(i, _)| i);
+    mapped.push((end_byte, out.len()));
+
+    let map = |byte: usize| {
+        let at = mapped.partition_point(|&(i, _)| i < byte);
+        mapped.get(at).map_or(out.len(), |&(_, to)| to)
+    };
+    let highlights = matches
+        .iter()
+        .map(|range| map(range.start)..map(range.end))
+        .filter(|range| range.start < range.end)
+        .collect();
+
+    if end_byte < text.len() {
+        out.push_str("...");
+    }
+    Snippet { text: out, highlights }
+}
+
+/// Snippets around what `matcher` finds in `text` within `scopes`, at most
+/// `limit` of them. Each section's first snippet is labelled with its scope
+/// unless it is plain text.
+/// Falls back to the start of the message when nothing is found.
+pub fn message_snippet(
+    text: &MessageText,
+    matcher: &Matcher,
+    scopes: Scopes,
+    context: usize,
+    limit: usize,
+) -> Snippet {
+    let snippets: Vec<Snippet> = text
+        .sections()
+        .filter(|(scope, _)| scopes.contains(*scope))
+        .flat_map(|(scope, content)| {
+            extract(content, &matcher.find(content), context)
+                .into_iter()
+                .enumerate()
+                .map(move |(i, snippet)| if i == 0 { labelled(scope, snippet) } else { snippet })
+        })
+        .take(limit)
+        .collect();
+    if snippets.is_empty() {
+        Snippet::leading(&text.display_text(scopes), context * 2)
+    } else {
+        Snippet::join(&snippets)
+    }
+}
+
+/// One-line preview of a search result: the first snippet of its best
+/// matching message, or the start of its first message when nothing matched
+/// (e.g. an empty query).
+pub fn result_preview(result: &SearchResult, matcher: &Matcher, scopes: Scopes, width: usize) -> Snippet {
+    let matched = result.ranked_texts().into_iter().find_map(|(text, _, _)| {
+        text.sections()
+            .filter(|(scope, _)| scopes.contains(*scope))
+            .find_map(|(scope, content)| {
+                let snippet = extract(content, &matcher.find(content), width / 2).into_iter().next()?;
+                Some(labelled(scope, snippet))
+            })
+    });
+    matched
+        .or_else(|| {
+            // Summaries already serve as the session title
+            let text = result
+                .texts()
+                .find(|text| text.kind != "summary")
+                .or_else(|| result.texts().next())?;
+            let (scope, content) = text
+                .sections()
+                .find(|(scope, _)| *scope == Scope::Text)
+                .or_else(|| text.sections().next())?;
+            Some(labelled(scope, Snippet::leading(content, width)))
+        })
+        .unwrap_or_else(|| Snippet {
+            text: "No preview available".to_string(),
+            highlights: Vec::new(),
+        })
+}
+
+/// Prefixes a snippet with `[scope] ` for anything but plain text.
+fn labelled(scope: Scope, snippet: Snippet) -> Snippet {
+    if scope == Scope::Text {
+        return snippet;
+    }
+    let label = format!("[{}] ", scope.label());
+    Snippet {
+        highlights: snippet
+            .highlights
+            .into_iter()
+            .map(|range| range.start + label.len()..range.end + label.len())
+            .collect(),
+        text: label + &snippet.text,
+    }
+}
@@ -0,0 +1,362 @@
+use crate::pricing::{Budget, PricingTable};
+use crate::types::{MessageText, SearchResult, TokenUsage};
+use chrono::{DateTime, Datelike, Duration, Local, NaiveDate, Timelike};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+
+/// Token usage and estimated cost of a set of sessions, totalled per
+/// session, project, model and day.
+#[derive(Debug, Clone, Default)]
+pub struct UsageStats {
+    pub total: TokenUsage,
+    /// Estimated cost in US dollars of the responses whose model is priced.
+    pub cost: f64,
+    /// API responses counted.
+    pub responses: usize,
+    /// Responses from models the pricing table does not list.
+    pub unpriced: usize,
+    /// Rows of each breakdown are sorted by total tokens, largest first.
+    pub sessions: Vec<UsageRow>,
+    pub projects: Vec<UsageRow>,
+    pub models: Vec<UsageRow>,
+    /// Usage per local calendar day.
+    pub days: BTreeMap<NaiveDate, UsageRow>,
+    /// Spend above which sessions and days are highlighted.
+    pub budget: Budget,
+}
+
+/// Usage of one session, project, model, day or month.
+#[derive(Debug, Clone, Default)]
+pub struct UsageRow {
+    pub name: String,
+    pub tokens: TokenUsage,
+    pub cost: f64,
+}
+
+impl UsageStats {
+    /// Totals every response in the sessions of `results`, whether or not
+    /// the response itself matched the search.
+    pub fn collect(results: &[SearchResult], pricing: &PricingTable) -> Self {
+        let mut stats = UsageStats {
+            budget: pricing.budget,
+            ..UsageStats::default()
+        };
+        let mut projects: HashMap<&str, UsageRow> = HashMap::new();
+        let mut models: HashMap<&str, UsageRow> = HashMap::new();
+
+        for result in results {
+            let project = &result.project;
+            let mut session = UsageRow::named(project.label());
+            for text in &project.texts {
+                let Some(tokens) = text.tokens else { continue };
+                let model = text.model.as_deref();
+                let cost = pricing.message_cost(text).unwrap_or_else(|| {
+                    stats.unpriced += 1;
+                    0.0
+                });
+                stats.total += tokens;
+                stats.cost += cost;
+                stats.responses += 1;
+                session.add(tokens, cost);
+                projects
+                    .entry(&project.project_path)
+                    .or_insert_with(|| UsageRow::named(&project.short_name))
+                    .add(tokens, cost);
+                let model = model.unwrap_or("unknown");
+                models.entry(model).or_insert_with(|| UsageRow::named(model)).add(tokens, cost);
+                if let Some(day) = local_date(text) {
+                    stats
+                        .days
+                        .entry(day)
+                        .or_insert_with(|| UsageRow::named(&day.to_string()))
+                        .add(tokens, cost);
+                }
+            }
+            if session.tokens != TokenUsage::default() {
+                stats.sessions.push(session);
+            }
+        }
+
+        stats.projects = projects.into_values().collect();
+        stats.models = models.into_values().collect();
+        for rows in [&mut stats.sessions, &mut stats.projects, &mut stats.models] {
+            rows.sort_by(|a, b| b.tokens.total().cmp(&a.tokens.total()).then_with(|| a.name.cmp(&b.name)));
+        }
+        stats
+    }
+
+    /// Total tokens of each of the `count` days up to the last day with any
+    /// usage, oldest first. Days without usage are zero.
+    pub fn daily_volume(&self, count: usize) -> Vec<(NaiveDate, u64)> {
+        let Some(&last) = self.days.keys().next_back() else {
+            return Vec::new();
+        };
+        (0..count as i64)
+            .rev()
+            .map(|back| {
+                let day = last - Duration::days(back);
+                (day, self.days.get(&day).map_or(0, |row| row.tokens.total()))
+            })
+            .collect()
+    }
+
+    /// The days with usage as rows, most recent first.
+    pub fn day_rows(&self) -> Vec<UsageRow> {
+        self.days.values().rev().cloned().collect()
+    }
+
+    /// Usage per calendar month (`YYYY-MM`), most recent first.
+    pub fn month_rows(&self) -> Vec<UsageRow> {
+        let mut months: BTreeMap<(i32, u32), UsageRow> = BTreeMap::new();
+        for (day, row) in &self.days {
+            months
+                .entry((day.year(), day.month()))
+                .or_insert_with(|| UsageRow::named(&day.format("%Y-%m").to_string()))
+                .add(row.tokens, row.cost);
+        }
+        months.into_values().rev().collect()
+    }
+
+    /// Sessions whose estimated cost exceeds the session budget.
+    pub fn sessions_over_budget(&self) -> usize {
+        self.budget.session.map_or(0, |limit| self.sessions.iter().filter(|row| row.cost > limit).count())
+    }
+
+    /// Days whose estimated cost exceeds the daily budget.
+    pub fn days_over_budget(&self) -> usize {
+        self.budget.day.map_or(0, |limit| self.days.values().filter(|row| row.cost > limit).count())
+    }
+}
+
+impl UsageRow {
+    fn named(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            ..Self::default()
+        }
+    }
+
+    fn add(&mut self, tokens: TokenUsage, cost: f64) {
+        self.tokens += tokens;
+        self.cost += cost;
+    }
+}
+
+/// How often tools were called in a set of sessions and how often the
+/// calls failed, per tool and per project.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ToolStats {
+    pub calls: usize,
+    pub failures: usize,
+    /// Each tool with the projects it was called in, most used first.
+    pub tools: Vec<ToolRow>,
+    /// Each project with the tools called in it, busiest first.
+    pub projects: Vec<ToolRow>,
+}
+
+/// Calls of one tool or in one project, broken down by project or tool.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ToolRow {
+    pub name: String,
+    pub calls: usize,
+    /// Calls whose result was an error (`is_error`).
+    pub failures: usize,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub breakdown: Vec<ToolRow>,
+}
+
+impl ToolStats {
+    /// Counts every tool call in the sessions of `results`. A failure is
+    /// attributed to the tool whose call id its result answers.
+    pub fn collect(results: &[SearchResult]) -> Self {
+        // (tool, project path) -> (calls, failures)
+        let mut counts: HashMap<(&str, &str), (usize, usize)> = HashMap::new();
+        let mut project_names: HashMap<&str, &str> = HashMap::new();
+
+        for result in results {
+            let project = &result.project;
+            let path = project.project_path.as_str();
+            project_names.insert(path, &project.short_name);
+            let calls: HashMap<&str, &str> = project
+                .texts
+                .iter()
+                .flat_map(|text| text.tool_ids.iter().map(String::as_str).zip(text.tools.iter().map(String::as_str)))
+                .collect();
+            for text in &project.texts {
+                for tool in &text.tools {
+                    counts.entry((tool, path)).or_default().0 += 1;
+                }
+                for id in &text.failed_tools {
+                    let tool = calls.get(id.as_str()).copied().unwrap_or("unknown");
+                    counts.entry((tool, path)).or_default().1 += 1;
+                }
+            }
+        }
+
+        let mut stats = ToolStats::default();
+        let mut tools: HashMap<&str, ToolRow> = HashMap::new();
+        let mut projects: HashMap<&str, ToolRow> = HashMap::new();
+        for ((tool, path), (calls, failures)) in counts {
+            let project = project_names.get(path).copied().unwrap_or(path);
+            stats.calls += calls;
+            stats.failures += failures;
+            tools.entry(tool).or_insert_with(|| ToolRow::named(tool)).add(project, calls, failures);
+            projects.entry(path).or_insert_with(|| ToolRow::named(project)).add(tool, calls, failures);
+        }
+        stats.tools = ToolRow::sorted(tools.into_values().collect());
+        stats.projects = ToolRow::sorted(projects.into_values().collect());
+        stats
+    }
+
+    /// Share of all calls that failed, `None` without calls.
+    pub fn failure_rate(&self) -> Option<f64> {
+        (self.calls > 0).then(|| self.failures as f64 / self.calls as f64)
+    }
+}
+
+impl ToolRow {
+    fn named(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            ..Self::default()
+        }
+    }
+
+    fn add(&mut self, name: &str, calls: usize, failures: usize) {
+        self.calls += calls;
+        self.failures += failures;
+        self.breakdown.push(ToolRow {
+            name: name.to_string(),
+            calls,
+            failures,
+            breakdown: Vec::new(),
+        });
+    }
+
+    /// Rows and their breakdowns by calls, most first.
+    fn sorted(mut rows: Vec<ToolRow>) -> Vec<ToolRow> {
+        for row in &mut rows {
+            row.breakdown = Self::sorted(std::mem::take(&mut row.breakdown));
+        }
+        rows.sort_by(|a, b| b.calls.cmp(&a.calls).then_with(|| a.name.cmp(&b.name)));
+        rows
+    }
+
+    /// Share of calls that failed, `None` without calls.
+    pub fn failure_rate(&self) -> Option<f64> {
+        (self.calls > 0).then(|| self.failures as f64 / self.calls as f64)
+    }
+}
+
+/// When messages were written, in local time: per day, per hour of the
+/// day, and the span of each session.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ActivityStats {
+    /// Messages with a timestamp.
+    pub messages: usize,
+    pub days: BTreeMap<NaiveDate, usize>,
+    /// Messages written in each hour of the day, from 0:00 to 23:00.
+    pub hours: [usize; 24],
+    /// Sessions by their first message, earliest first.
+    pub sessions: Vec<SessionSpan>,
+}
+
+/// First and last message of a session.
+#[derive(Debug, Clone, Serialize)]
+pub struct SessionSpan {
+    pub session_id: String,
+    pub title: String,
+    pub project: String,
+    pub start: DateTime<Local>,
+    pub end: DateTime<Local>,
+    pub messages: usize,
+}
+
+impl ActivityStats {
+    /// Counts every message in the sessions of `results`.
+    pub fn collect(results: &[SearchResult]) -> Self {
+        let mut stats = ActivityStats::default();
+        for result in results {
+            let project = &result.project;
+            let times: Vec<DateTime<Local>> =
+                project.texts.iter().filter_map(|text| Some(text.time?.with_timezone(&Local))).collect();
+            for time in &times {
+                stats.messages += 1;
+                *stats.days.entry(time.date_naive()).or_default() += 1;
+                stats.hours[time.hour() as usize] += 1;
+            }
+            if let (Some(&start), Some(&end)) = (times.iter().min(), times.iter().max()) {
+                stats.sessions.push(SessionSpan {
+                    session_id: project.session_id().to_string(),
+                    title: project.label().to_string(),
+                    project: project.short_name.clone(),
+                    start,
+                    end,
+                    messages: times.len(),
+                });
+            }
+        }
+        stats.sessions.sort_by_key(|span| span.start);
+        stats
+    }
+
+    /// Messages on `day`.
+    pub fn on(&self, day: NaiveDate) -> usize {
+        self.days.get(&day).copied().unwrap_or(0)
+    }
+
+    /// The most recent day with activity.
+    pub fn last_day(&self) -> Option<NaiveDate> {
+        self.days.keys().next_back().copied()
+    }
+
+    /// `date,messages` for each day with activity, oldest first.
+    pub fn days_csv(&self) -> String {
+        let mut csv = String::from("date,messages\n");
+        for (day, count) in &self.days {
+            csv.push_str(&format!("{},{}\n", day, count));
+        }
+        csv
+    }
+
+    /// `hour,messages` for each hour of the day.
+    pub fn hours_csv(&self) -> String {
+        let mut csv = String::from("hour,messages\n");
+        for (hour, count) in self.hours.iter().enumerate() {
+            csv.push_str(&format!("{},{}\n", hour, count));
+        }
+        csv
+    }
+
+    /// One row per session with its first and last message in RFC 3339.
+    pub fn sessions_csv(&self) -> String {
+        let mut csv = String::from("session_id,project,title,start,end,minutes,messages\n");
+        for span in &self.sessions {
+            csv.push_str(&format!(
+                "{},{},{},{},{},{},{}\n",
+                csv_field(&span.session_id),
+                csv_field(&span.project),
+                csv_field(&span.title),
+                span.start.to_rfc3339(),
+                span.end.to_rfc3339(),
+                (span.end - span.start).num_minutes(),
+                span.messages,
+            ));
+        }
+        csv
+    }
+}
+
+/// Quotes a CSV field when it contains a separator, quote or line break.
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// Day the message was written on, in local time.
+fn local_date(text: &MessageText) -> Option<NaiveDate> {
+    Some(text.time?.with_timezone(&Local).date_naive())
+}
@@ -0,0 +1,159 @@
+use crate::types::MessageText;
+use std::collections::HashMap;
+
+/// The messages of a session file arranged by `parent_uuid`.
+///
+/// Editing or retrying a prompt starts a new child of the same parent, so a
+/// session is a tree rather than a list. Each path from the root to a leaf is
+/// one branch of the conversation. Messages are referred to by their index in
+/// `ProjectFile::texts`; records without a uuid (summaries, unknown types) are
+/// not part of the tree.
+#[derive(Debug, Clone, Default)]
+pub struct Thread {
+    parents: Vec<Option<usize>>,
+    children: Vec<Vec<usize>>,
+    /// Whether each message is in the tree at all.
+    members: Vec<bool>,
+    sidechain: Vec<bool>,
+}
+
+impl Thread {
+    pub fn build(texts: &[MessageText]) -> Self {
+        let mut by_uuid: HashMap<&str, usize> = HashMap::new();
+        let mut members = vec![false; texts.len()];
+        for (i, text) in texts.iter().enumerate() {
+            // A record repeated with the same uuid (e.g. after a resume) is not a new branch
+            if let Some(uuid) = text.uuid.as_deref()
+                && !by_uuid.contains_key(uuid)
+            {
+                by_uuid.insert(uuid, i);
+                members[i] = true;
+            }
+        }
+
+        let mut parents = vec![None; texts.len()];
+        let mut children = vec![Vec::new(); texts.len()];
+        for (i, text) in texts.iter().enumerate().filter(|(i, _)| members[*i]) {
+            // Records are appended, so a parent comes first. Parents outside
+            // this file (or out of order, which would allow cycles) make the
+            // message a root
+            if let Some(&parent) = text.parent_uuid.as_deref().and_then(|uuid| by_uuid.get(uuid))
+                && parent < i
+            {
+                parents[i] = Some(parent);
+                children[parent].push(i);
+            }
+        }
+
+        Self {
+            parents,
+            children,
+            members,
+            sidechain: texts.iter().map(|text| text.is_sidechain).collect(),
+        }
+    }
+
+    pub fn contains(&self, message: usize) -> bool {
+        self.members.get(message).copied().unwrap_or(false)
+    }
+
+    pub fn parent(&self, message: usize) -> Option<usize> {
+        self.parents.get(message).copied().flatten()
+    }
+
+    /// Messages that continue `message`, oldest first. Sub-agent messages
+    /// only continue each other.
+    pub fn children(&self, message: usize) -> Vec<usize> {
+        self.children
+            .get(message)
+            .into_iter()
+            .flatten()
+            .copied()
+            .filter(|&child| self.sidechain[child] == self.sidechain[message])
+            .collect()
+    }
+
+    /// `message` and the other continuations of its parent, oldest first.
+    pub fn alternatives(&self, message: usize) -> Vec<usize> {
+        match self.parent(message) {
+            Some(parent) => self.children(parent),
+            None => vec![message],
+        }
+    }
+
+    /// The alternative after (or before) `message`, wrapping around.
+    pub fn next_alternative(&self, message: usize, forward: bool) -> usize {
+        let alternatives = self.alternatives(message);
+        let len = alternatives.len().max(1);
+        let at = alternatives.iter().position(|&m| m == message).unwrap_or(0);
+        let next = if forward { (at + 1) % len } else { (at + len - 1) % len };
+        alternatives.get(next).copied().unwrap_or(message)
+    }
+
+    /// Messages with more than one continuation.
+    pub fn forks(&self) -> Vec<usize> {
+        (0..self.members.len())
+            .filter(|&message| self.children(message).len() > 1)
+            .collect()
+    }
+
+    /// Last message of every branch of the main conversation, oldest first.
+    pub fn leaves(&self) -> Vec<usize> {
+        (0..self.members.len())
+            .filter(|&message| self.contains(message) && !self.sidechain[message] && self.children(message).is_empty())
+            .collect()
+    }
+
+    /// Number of messages written by sub-agents.
+    pub fn sidechain_count(&self) -> usize {
+        (0..self.members.len())
+            .filter(|&message| self.contains(message) && self.sidechain[message])
+            .count()
+    }
+
+    /// The leaf reached from `message` by always taking the newest
+    /// continuation.
+    pub fn latest_leaf(&self, message: usize) -> usize {
+        let mut current = message;
+        while let Some(&next) = self.children(current).last() {
+            current = next;
+        }
+        current
+    }
+
+    /// Leaf of the branch the session ended on: the newest main-conversation
+    /// message, followed to its newest continuation.
+    pub fn main_leaf(&self) -> Option<usize> {
+        (0..self.members.len())
+            .rev()
+            .find(|&message| self.contains(message) && !self.sidechain[message])
+            .map(|message| self.latest_leaf(message))
+    }
+
+    /// Number of messages above `message`, i.e. its position on any branch
+    /// through it.
+    pub fn depth(&self, message: usize) -> usize {
+        let mut depth = 0;
+        let mut current = message;
+        while let Some(parent) = self.parent(current) {
+            depth += 1;
+            current = parent;
+        }
+        depth
+    }
+
+    /// Messages from the root down to `leaf`.
+    pub fn branch(&self, leaf: usize) -> Vec<usize> {
+        if !self.contains(leaf) {
+            return Vec::new();
+        }
+        let mut branch = vec![leaf];
+        let mut current = leaf;
+        while let Some(parent) = self.parent(current) {
+            branch.push(parent);
+            current = parent;
+        }
+        branch.reverse();
+        branch
+    }
+}
@@ -0,0 +1,122 @@
+use crate::types::{MessageText, Scope};
+use regex::Regex;
+use std::collections::HashMap;
+use std::sync::LazyLock;
+
+/// Longest title kept, in characters.
+const MAX_LEN: usize = 80;
+
+static TAG: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"</?[a-zA-Z][\w-]*>").unwrap());
+static COMMAND_NAME: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"<command-name>\s*(\S+?)\s*</command-name>").unwrap());
+static COMMAND_ARGS: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?s)<command-args>(.*?)</command-args>").unwrap());
+
+/// Title of a session: the summary whose `leafUuid` points at a message of
+/// this session (the one for the latest leaf when there are several), else
+/// the first prompt typed by the user, else any summary in the file.
+///
+/// Only the session's own file is looked at; [`session_titles`] also finds
+/// summaries written into other files.
+pub fn session_title(texts: &[MessageText]) -> Option<String> {
+    session_titles(&[texts]).pop().flatten()
+}
+
+/// Titles of several sessions, resolved together. Claude Code often writes
+/// the summary of a session at the top of the next session's file, with its
+/// `leafUuid` pointing back, so each summary titles the session holding its
+/// leaf, whichever file it is in. A leaf found in the summary's own file
+/// wins over copies of it in other files (resumed sessions repeat earlier
+/// messages).
+pub fn session_titles(sessions: &[&[MessageText]]) -> Vec<Option<String>> {
+    // uuid -> (session, position in it)
+    let mut messages: HashMap<&str, (usize, usize)> = HashMap::new();
+    for (session, texts) in sessions.iter().enumerate() {
+        for (at, text) in texts.iter().enumerate() {
+            if let Some(uuid) = text.uuid.as_deref() {
+                messages.entry(uuid).or_insert((session, at));
+            }
+        }
+    }
+
+    // The summary for the latest leaf of each session, and the first summary
+    // of each file whose leaf was not found at all
+    let mut linked: Vec<Option<(usize, &MessageText)>> = vec![None; sessions.len()];
+    let mut unlinked: Vec<Option<&MessageText>> = vec![None; sessions.len()];
+    for (session, texts) in sessions.iter().enumerate() {
+        for summary in texts.iter().filter(|text| text.kind == "summary") {
+            let leaf = summary.leaf_uuid.as_deref();
+            let target = texts
+                .iter()
+                .position(|text| leaf.is_some() && text.uuid.as_deref() == leaf)
+                .map(|at| (session, at))
+                .or_else(|| leaf.and_then(|leaf| messages.get(leaf).copied()));
+            match target {
+                Some((target, at)) => {
+                    if linked[target].is_none_or(|(latest, _)| at >= latest) {
+                        linked[target] = Some((at, summary));
+                    }
+                }
+                None => {
+                    unlinked[session].get_or_insert(summary);
+                }
+            }
+        }
+    }
+
+    sessions
+        .iter()
+        .enumerate()
+        .map(|(session, texts)| {
+            linked[session]
+                .and_then(|(_, summary)| summary_text(summary))
+                .or_else(|| first_prompt(texts))
+                .or_else(|| unlinked[session].and_then(summary_text))
+        })
+        .collect()
+}
+
+fn summary_text(summary: &MessageText) -> Option<String> {
+    summary.content.first().and_then(|text| clean(text))
+}
+
+fn first_prompt(texts: &[MessageText]) -> Option<String> {
+    texts
+        .iter()
+        .filter(|text| text.kind == "user" && !text.is_sidechain)
+        .flat_map(|text| text.sections())
+        .filter(|(scope, _)| *scope == Scope::Text)
+        .find_map(|(_, text)| clean_prompt(text))
+}
+
+/// The prompt as a one-line title, or `None` for text the client injects
+/// (command output, caveats, interruptions). Slash commands become
+/// `/name args`.
+pub fn clean_prompt(prompt: &str) -> Option<String> {
+    let prompt = prompt.trim();
+    if let Some(name) = COMMAND_NAME.captures(prompt) {
+        let args = COMMAND_ARGS.captures(prompt).map_or("", |args| args.get(1).map_or("", |m| m.as_str()));
+        return clean(&format!("{} {}", &name[1], args));
+    }
+    if prompt.starts_with("<local-command-")
+        || prompt.starts_with("Caveat:")
+        || prompt.starts_with("[Request interrupted")
+    {
+        return None;
+    }
+    clean(&TAG.replace_all(prompt, " "))
+}
+
+/// Collapses whitespace and cuts to [`MAX_LEN`] characters.
+fn clean(text: &str) -> Option<String> {
+    let words: Vec<&str> = text.split_whitespace().collect();
+    if words.is_empty() {
+        return None;
+    }
+    let line = words.join(" ");
+    if line.chars().count() <= MAX_LEN {
+        return Some(line);
+    }
+    let cut: String = line.chars().take(MAX_LEN - 1).collect();
+    Some(format!("{}…", cut.trim_end()))
+}
@@ -1,5 +1,10 @@
-use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use crate::paths;
+use crate::title;
+use chrono::{DateTime, Utc};
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use std::path::Path;
+use std::sync::Arc;
+use std::time::SystemTime;
 
 // ============================================
 // Base Types
@@ -23,15 +28,19 @@ pub struct BaseMessage {
 // ============================================
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(tag = "type", rename_all = "snake_case")]
+#[serde(tag = "type", rename_all = "snake_case", remote = "Self")]
 pub enum Content {
     Text {
         text: String,
+        #[serde(flatten)]
+        extra: serde_json::Map<String, serde_json::Value>,
     },
     ToolUse {
         id: String,
         name: String,
-        input: HashMap<String, serde_json::Value>,
+        input: serde_json::Map<String, serde_json::Value>,
+        #[serde(flatten)]
+        extra: serde_json::Map<String, serde_json::Value>,
     },
     ToolResult {
         tool_use_id: String,
@@ -39,36 +48,102 @@ pub enum Content {
         content: Option<ToolResultContent>,
         #[serde(skip_serializing_if = "Option::is_none")]
         is_error: Option<bool>,
+        #[serde(flatten)]
+        extra: serde_json::Map<String, serde_json::Value>,
     },
     Thinking {
         thinking: String,
-        signature: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        signature: Option<String>,
+        #[serde(flatten)]
+        extra: serde_json::Map<String, serde_json::Value>,
     },
     Image {
         source: ImageSource,
+        #[serde(flatten)]
+        extra: serde_json::Map<String, serde_json::Value>,
     },
+    /// A content block type this viewer does not know about, kept verbatim.
+    #[serde(skip)]
+    Unknown {
+        kind: String,
+        raw: serde_json::Value,
+    },
+}
+
+impl<'de> Deserialize<'de> for Content {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let raw = serde_json::Value::deserialize(deserializer)?;
+        match record_kind(&raw)? {
+            "text" | "tool_use" | "tool_result" | "thinking" | "image" => {
+                Content::deserialize(raw).map_err(de::Error::custom)
+            }
+            kind => Ok(Content::Unknown {
+                kind: kind.to_string(),
+                raw,
+            }),
+        }
+    }
+}
+
+impl Serialize for Content {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Content::Unknown { raw, .. } => raw.serialize(serializer),
+            _ => Content::serialize(self, serializer),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(untagged)]
 pub enum ToolResultContent {
     String(String),
-    TextArray(Vec<TextContent>),
-    ImageArray(Vec<ImageContent>),
+    Array(Vec<ToolResultItem>),
 }
 
+/// One block of a tool result given as an array. Text and images can be
+/// mixed, and other block types are kept verbatim like in `Content`.
 #[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct TextContent {
-    #[serde(rename = "type")]
-    pub content_type: String,
-    pub text: String,
+#[serde(tag = "type", rename_all = "snake_case", remote = "Self")]
+pub enum ToolResultItem {
+    Text {
+        text: String,
+        #[serde(flatten)]
+        extra: serde_json::Map<String, serde_json::Value>,
+    },
+    Image {
+        source: ImageSource,
+        #[serde(flatten)]
+        extra: serde_json::Map<String, serde_json::Value>,
+    },
+    #[serde(skip)]
+    Unknown {
+        kind: String,
+        raw: serde_json::Value,
+    },
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct ImageContent {
-    #[serde(rename = "type")]
-    pub content_type: String,
-    pub source: ImageSource,
+impl<'de> Deserialize<'de> for ToolResultItem {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let raw = serde_json::Value::deserialize(deserializer)?;
+        match record_kind(&raw)? {
+            "text" | "image" => ToolResultItem::deserialize(raw).map_err(de::Error::custom),
+            kind => Ok(ToolResultItem::Unknown {
+                kind: kind.to_string(),
+                raw,
+            }),
+        }
+    }
+}
+
+impl Serialize for ToolResultItem {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            ToolResultItem::Unknown { raw, .. } => raw.serialize(serializer),
+            _ => ToolResultItem::serialize(self, serializer),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -79,41 +154,59 @@ pub struct ImageSource {
     pub data: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub media_type: Option<String>,
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
 }
 
 // ============================================
 // Usage Schema
 // ============================================
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+// Older logs omit the cache counters and newer ones add fields we don't model
+// yet. Counters are only written back when the record had them, and unknown
+// keys are kept in `extra`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub struct Usage {
-    pub input_tokens: u64,
-    pub cache_creation_input_tokens: u64,
-    pub cache_read_input_tokens: u64,
-    pub output_tokens: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub input_tokens: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cache_creation_input_tokens: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cache_read_input_tokens: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output_tokens: Option<u64>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub service_tier: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub server_tool_use: Option<ServerToolUse>,
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct ServerToolUse {
-    pub web_search_requests: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub web_search_requests: Option<u64>,
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
 }
 
 // ============================================
 // Message Types
 // ============================================
 
+// Every record keeps the fields it has beyond the ones modelled here in
+// `extra`, so records written back out match what was read.
 #[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(tag = "type", rename_all = "snake_case")]
+#[serde(tag = "type", rename_all = "snake_case", remote = "Self")]
 pub enum SessionMessage {
     Summary {
         summary: String,
         #[serde(rename = "leafUuid")]
         leaf_uuid: String,
+        #[serde(flatten)]
+        extra: serde_json::Map<String, serde_json::Value>,
     },
     System {
         #[serde(flatten)]
@@ -125,41 +218,119 @@ pub enum SessionMessage {
         tool_use_id: Option<String>,
         #[serde(skip_serializing_if = "Option::is_none")]
         level: Option<String>,
-        #[serde(rename = "gitBranch", skip_serializing_if = "Option::is_none")]
-        git_branch: Option<String>,
+        #[serde(rename = "gitBranch", default, deserialize_with = "present", skip_serializing_if = "Option::is_none")]
+        git_branch: Option<Option<String>>,
         #[serde(rename = "requestId", skip_serializing_if = "Option::is_none")]
         request_id: Option<String>,
+        #[serde(flatten)]
+        extra: serde_json::Map<String, serde_json::Value>,
     },
     User {
         #[serde(flatten)]
         base: BaseMessage,
         message: UserMessage,
-        #[serde(rename = "gitBranch", skip_serializing_if = "Option::is_none")]
-        git_branch: Option<String>,
+        #[serde(rename = "gitBranch", default, deserialize_with = "present", skip_serializing_if = "Option::is_none")]
+        git_branch: Option<Option<String>>,
         #[serde(rename = "isMeta", skip_serializing_if = "Option::is_none")]
         is_meta: Option<bool>,
         #[serde(rename = "isCompactSummary", skip_serializing_if = "Option::is_none")]
         is_compact_summary: Option<bool>,
         #[serde(rename = "toolUseResult", skip_serializing_if = "Option::is_none")]
         tool_use_result: Option<serde_json::Value>,
+        #[serde(flatten)]
+        extra: serde_json::Map<String, serde_json::Value>,
     },
     Assistant {
         #[serde(flatten)]
         base: BaseMessage,
-        message: AssistantMessage,
+        message: Box<AssistantMessage>,
         #[serde(rename = "requestId", skip_serializing_if = "Option::is_none")]
         request_id: Option<String>,
-        #[serde(rename = "gitBranch", skip_serializing_if = "Option::is_none")]
-        git_branch: Option<String>,
+        #[serde(rename = "gitBranch", default, deserialize_with = "present", skip_serializing_if = "Option::is_none")]
+        git_branch: Option<Option<String>>,
         #[serde(rename = "isApiErrorMessage", skip_serializing_if = "Option::is_none")]
         is_api_error_message: Option<bool>,
+        #[serde(flatten)]
+        extra: serde_json::Map<String, serde_json::Value>,
+    },
+    /// A record type this viewer does not know about, kept verbatim so it
+    /// still shows up in timelines and round-trips unchanged.
+    #[serde(skip)]
+    Unknown {
+        kind: String,
+        raw: serde_json::Value,
     },
 }
 
+impl<'de> Deserialize<'de> for SessionMessage {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let raw = serde_json::Value::deserialize(deserializer)?;
+        match record_kind(&raw)? {
+            "summary" | "system" | "user" | "assistant" => {
+                SessionMessage::deserialize(raw).map_err(de::Error::custom)
+            }
+            kind => Ok(SessionMessage::Unknown {
+                kind: kind.to_string(),
+                raw,
+            }),
+        }
+    }
+}
+
+impl Serialize for SessionMessage {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            SessionMessage::Unknown { raw, .. } => raw.serialize(serializer),
+            _ => SessionMessage::serialize(self, serializer),
+        }
+    }
+}
+
+/// Returns the `type` tag of a JSON record.
+fn record_kind<E: de::Error>(raw: &serde_json::Value) -> Result<&str, E> {
+    match raw.get("type") {
+        Some(serde_json::Value::String(kind)) => Ok(kind),
+        Some(_) => Err(E::custom("field `type` is not a string")),
+        None if raw.is_object() => Err(E::missing_field("type")),
+        None => Err(E::custom("expected a JSON object")),
+    }
+}
+
+/// Collects the human-readable strings of a record we have no schema for.
+fn unknown_text_fields(value: &serde_json::Value, texts: &mut Vec<String>) {
+    match value {
+        serde_json::Value::Object(map) => {
+            for (key, value) in map {
+                match value {
+                    serde_json::Value::String(text) => {
+                        // Skip identifiers and bookkeeping; anything else may be prose
+                        if !matches!(
+                            key.as_str(),
+                            "type" | "uuid" | "parentUuid" | "leafUuid" | "sessionId" | "requestId"
+                                | "id" | "timestamp" | "version" | "userType" | "cwd" | "signature"
+                        ) {
+                            texts.push(text.clone());
+                        }
+                    }
+                    _ => unknown_text_fields(value, texts),
+                }
+            }
+        }
+        serde_json::Value::Array(values) => {
+            for value in values {
+                unknown_text_fields(value, texts);
+            }
+        }
+        _ => {}
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct UserMessage {
     pub role: String,
     pub content: UserContent,
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -169,17 +340,42 @@ pub enum UserContent {
     Array(Vec<Content>),
 }
 
+// Fields missing from a record stay missing when it is written back, so
+// they are all optional. `stop_reason` and `stop_sequence` are usually
+// present as `null`, which is told apart from absent.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AssistantMessage {
-    pub id: String,
-    #[serde(rename = "type")]
-    pub message_type: String,
-    pub role: String,
-    pub model: String,
-    pub content: Vec<Content>,
-    pub stop_reason: Option<String>,
-    pub stop_sequence: Option<String>,
-    pub usage: Usage,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    #[serde(rename = "type", default, skip_serializing_if = "Option::is_none")]
+    pub message_type: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub role: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub content: Option<Vec<Content>>,
+    #[serde(default, deserialize_with = "present", skip_serializing_if = "Option::is_none")]
+    pub stop_reason: Option<Option<String>>,
+    #[serde(default, deserialize_with = "present", skip_serializing_if = "Option::is_none")]
+    pub stop_sequence: Option<Option<String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub usage: Option<Usage>,
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+impl AssistantMessage {
+    /// The content blocks, empty if the record has none.
+    pub fn content(&self) -> &[Content] {
+        self.content.as_deref().unwrap_or_default()
+    }
+}
+
+/// `Some` for a field that is present, even when it is `null`; missing
+/// fields fall back to `None` through `#[serde(default)]`.
+fn present<'de, D: Deserializer<'de>, T: Deserialize<'de>>(deserializer: D) -> Result<Option<T>, D::Error> {
+    T::deserialize(deserializer).map(Some)
 }
 
 // ============================================
@@ -193,18 +389,197 @@ impl SessionMessage {
             SessionMessage::System { .. } => "system",
             SessionMessage::User { .. } => "user",
             SessionMessage::Assistant { .. } => "assistant",
+            SessionMessage::Unknown { kind, .. } => kind,
         }
     }
 
+    /// The timestamp parsed, `None` if it is missing or malformed.
+    pub fn get_time(&self) -> Option<DateTime<Utc>> {
+        let timestamp = DateTime::parse_from_rfc3339(self.get_timestamp()?).ok()?;
+        Some(timestamp.with_timezone(&Utc))
+    }
+
     pub fn get_timestamp(&self) -> Option<&str> {
         match self {
             SessionMessage::Summary { .. } => None,
             SessionMessage::System { base, .. } => Some(&base.timestamp),
             SessionMessage::User { base, .. } => Some(&base.timestamp),
             SessionMessage::Assistant { base, .. } => Some(&base.timestamp),
+            SessionMessage::Unknown { raw, .. } => raw.get("timestamp").and_then(|t| t.as_str()),
         }
     }
 
+    pub fn get_session_id(&self) -> Option<&str> {
+        match self {
+            SessionMessage::Summary { .. } => None,
+            SessionMessage::System { base, .. }
+            | SessionMessage::User { base, .. }
+            | SessionMessage::Assistant { base, .. } => Some(&base.session_id),
+            SessionMessage::Unknown { raw, .. } => raw.get("sessionId").and_then(|t| t.as_str()),
+        }
+    }
+
+    pub fn get_git_branch(&self) -> Option<&str> {
+        match self {
+            SessionMessage::Summary { .. } => None,
+            SessionMessage::System { git_branch, .. }
+            | SessionMessage::User { git_branch, .. }
+            | SessionMessage::Assistant { git_branch, .. } => git_branch.as_ref()?.as_deref(),
+            SessionMessage::Unknown { raw, .. } => raw.get("gitBranch").and_then(|t| t.as_str()),
+        }
+    }
+
+    pub fn get_uuid(&self) -> Option<&str> {
+        match self {
+            SessionMessage::Summary { .. } => None,
+            SessionMessage::System { base, .. }
+            | SessionMessage::User { base, .. }
+            | SessionMessage::Assistant { base, .. } => Some(&base.uuid),
+            SessionMessage::Unknown { raw, .. } => raw.get("uuid").and_then(|t| t.as_str()),
+        }
+    }
+
+    pub fn get_parent_uuid(&self) -> Option<&str> {
+        match self {
+            SessionMessage::Summary { .. } => None,
+            SessionMessage::System { base, .. }
+            | SessionMessage::User { base, .. }
+            | SessionMessage::Assistant { base, .. } => base.parent_uuid.as_deref(),
+            SessionMessage::Unknown { raw, .. } => raw.get("parentUuid").and_then(|t| t.as_str()),
+        }
+    }
+
+    /// Message a summary record describes the conversation up to.
+    pub fn get_leaf_uuid(&self) -> Option<&str> {
+        match self {
+            SessionMessage::Summary { leaf_uuid, .. } => Some(leaf_uuid),
+            _ => None,
+        }
+    }
+
+    /// Whether the message belongs to a sub-agent conversation rather than
+    /// the main one.
+    pub fn is_sidechain(&self) -> bool {
+        match self {
+            SessionMessage::Summary { .. } => false,
+            SessionMessage::System { base, .. }
+            | SessionMessage::User { base, .. }
+            | SessionMessage::Assistant { base, .. } => base.is_sidechain,
+            SessionMessage::Unknown { raw, .. } => raw.get("isSidechain").and_then(|t| t.as_bool()).unwrap_or(false),
+        }
+    }
+
+    /// Working directory the message was written in.
+    pub fn get_cwd(&self) -> Option<&str> {
+        match self {
+            SessionMessage::Summary { .. } => None,
+            SessionMessage::System { base, .. }
+            | SessionMessage::User { base, .. }
+            | SessionMessage::Assistant { base, .. } => Some(&base.cwd),
+            SessionMessage::Unknown { raw, .. } => raw.get("cwd").and_then(|t| t.as_str()),
+        }
+    }
+
+    pub fn get_model(&self) -> Option<&str> {
+        match self {
+            SessionMessage::Assistant { message, .. } => message.model.as_deref(),
+            _ => None,
+        }
+    }
+
+    /// Token usage reported for an assistant message.
+    pub fn get_usage(&self) -> Option<&Usage> {
+        match self {
+            SessionMessage::Assistant { message, .. } => message.usage.as_ref(),
+            _ => None,
+        }
+    }
+
+    /// Id of the API response an assistant record belongs to. A response
+    /// with several content blocks is written as several records sharing it.
+    pub fn get_response_id(&self) -> Option<&str> {
+        match self {
+            SessionMessage::Assistant { message, .. } => message.id.as_deref(),
+            _ => None,
+        }
+    }
+
+    /// Names of the tools called by this message.
+    pub fn get_tool_names(&self) -> Vec<String> {
+        match self {
+            SessionMessage::Assistant { message, .. } => message
+                .content()
+                .iter()
+                .filter_map(|content| match content {
+                    Content::ToolUse { name, .. } => Some(name.clone()),
+                    _ => None,
+                })
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    /// Ids of the tool calls in the message, parallel to [`SessionMessage::get_tool_names`].
+    pub fn get_tool_use_ids(&self) -> Vec<String> {
+        match self {
+            SessionMessage::Assistant { message, .. } => message
+                .content()
+                .iter()
+                .filter_map(|content| match content {
+                    Content::ToolUse { id, .. } => Some(id.clone()),
+                    _ => None,
+                })
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    /// Ids of the tool calls whose results in this message are errors.
+    pub fn get_failed_tool_use_ids(&self) -> Vec<String> {
+        match self {
+            SessionMessage::User { message, .. } => match &message.content {
+                UserContent::Array(contents) => contents
+                    .iter()
+                    .filter_map(|content| match content {
+                        Content::ToolResult { tool_use_id, is_error: Some(true), .. } => Some(tool_use_id.clone()),
+                        _ => None,
+                    })
+                    .collect(),
+                UserContent::String(_) => Vec::new(),
+            },
+            _ => Vec::new(),
+        }
+    }
+
+    /// Everything searchable in the message, tagged with where it came from.
+    pub fn get_scoped_content(&self) -> Vec<(Scope, String)> {
+        match self {
+            SessionMessage::Summary { summary, .. } => vec![(Scope::Text, summary.clone())],
+            SessionMessage::System { content, .. } => vec![(Scope::System, content.clone())],
+            SessionMessage::User { message, is_meta, .. } => {
+                // Meta messages are injected by the client, not typed by the user
+                let text_scope = if *is_meta == Some(true) { Scope::System } else { Scope::Text };
+                match &message.content {
+                    UserContent::String(s) => vec![(text_scope, s.clone())],
+                    UserContent::Array(contents) => contents
+                        .iter()
+                        .flat_map(Content::get_scoped_content)
+                        .map(|(scope, text)| (if scope == Scope::Text { text_scope } else { scope }, text))
+                        .collect(),
+                }
+            }
+            SessionMessage::Assistant { message, .. } => message
+                .content()
+                .iter()
+                .flat_map(Content::get_scoped_content)
+                .collect(),
+            SessionMessage::Unknown { .. } => self
+                .get_text_content()
+                .into_iter()
+                .map(|text| (Scope::Text, text))
+                .collect(),
+        }
+    }
 
     pub fn get_text_content(&self) -> Vec<String> {
         match self {
@@ -212,36 +587,349 @@ impl SessionMessage {
             SessionMessage::System { content, .. } => vec![content.clone()],
             SessionMessage::User { message, .. } => match &message.content {
                 UserContent::String(s) => vec![s.clone()],
-                UserContent::Array(contents) => contents
+                UserContent::Array(contents) => contents.iter().flat_map(Content::get_text_content).collect(),
+            },
+            SessionMessage::Assistant { message, .. } => message
+                .content()
+                .iter()
+                .flat_map(Content::get_text_content)
+                .collect(),
+            SessionMessage::Unknown { raw, .. } => {
+                let mut texts = Vec::new();
+                unknown_text_fields(raw, &mut texts);
+                texts
+            }
+        }
+    }
+}
+
+impl Content {
+    /// Searchable text of this block with its scope. Tool inputs render as
+    /// `Name(value, ...)` with the input's values in key order.
+    pub fn get_scoped_content(&self) -> Vec<(Scope, String)> {
+        match self {
+            Content::ToolUse { name, input, .. } => {
+                let mut keys: Vec<&String> = input.keys().collect();
+                keys.sort();
+                let values: Vec<String> = keys
+                    .into_iter()
+                    .map(|key| match &input[key] {
+                        serde_json::Value::String(value) => value.clone(),
+                        value => value.to_string(),
+                    })
+                    .collect();
+                vec![(Scope::ToolInput, format!("{name}({})", values.join(", ")))]
+            }
+            Content::ToolResult { content, .. } => match content {
+                Some(ToolResultContent::String(text)) => vec![(Scope::ToolOutput, text.clone())],
+                Some(ToolResultContent::Array(items)) => items
                     .iter()
-                    .filter_map(|c| match c {
-                        Content::Text { text } => Some(text.clone()),
+                    .filter_map(|item| match item {
+                        ToolResultItem::Text { text, .. } => Some((Scope::ToolOutput, text.clone())),
                         _ => None,
                     })
                     .collect(),
+                None => Vec::new(),
             },
-            SessionMessage::Assistant { message, .. } => message
-                .content
-                .iter()
-                .filter_map(|c| match c {
-                    Content::Text { text } => Some(text.clone()),
-                    _ => None,
-                })
+            Content::Thinking { thinking, .. } => vec![(Scope::Thinking, thinking.clone())],
+            _ => self
+                .get_text_content()
+                .into_iter()
+                .map(|text| (Scope::Text, text))
                 .collect(),
         }
     }
+
+    /// Text shown and searched for this block.
+    pub fn get_text_content(&self) -> Vec<String> {
+        match self {
+            Content::Text { text, .. } => vec![text.clone()],
+            Content::Unknown { raw, .. } => {
+                let mut texts = Vec::new();
+                unknown_text_fields(raw, &mut texts);
+                texts
+            }
+            _ => Vec::new(),
+        }
+    }
 }
 
 // ============================================
 // Project File Structure
 // ============================================
 
+/// A line of a session file that could not be parsed into a [`SessionMessage`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ParseDiagnostic {
+    pub path: String,
+    /// 1-based line number.
+    pub line: usize,
+    /// Byte offset of the start of the line within the file.
+    pub byte_offset: u64,
+    pub error: String,
+    /// The record's `type` field, if the line was valid JSON and had one.
+    pub record_type: Option<String>,
+}
+
+/// Where a piece of searchable text comes from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Scope {
+    /// Prose written by the user or the assistant.
+    Text,
+    /// Arguments of a tool call, e.g. a Bash command or a file path.
+    ToolInput,
+    /// What a tool returned.
+    ToolOutput,
+    Thinking,
+    /// System records and messages injected by the client.
+    System,
+}
+
+impl Scope {
+    pub const ALL: [Scope; 5] = [
+        Scope::Text,
+        Scope::ToolInput,
+        Scope::ToolOutput,
+        Scope::Thinking,
+        Scope::System,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Scope::Text => "text",
+            Scope::ToolInput => "tool input",
+            Scope::ToolOutput => "tool output",
+            Scope::Thinking => "thinking",
+            Scope::System => "system",
+        }
+    }
+
+    fn bit(self) -> u8 {
+        1 << self as u8
+    }
+}
+
+/// A set of [`Scope`]s. Defaults to every scope.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Scopes(u8);
+
+impl Default for Scopes {
+    fn default() -> Self {
+        Self::ALL
+    }
+}
+
+impl Scopes {
+    pub const NONE: Scopes = Scopes(0);
+    pub const ALL: Scopes = Scopes(0b1_1111);
+
+    pub fn contains(self, scope: Scope) -> bool {
+        self.0 & scope.bit() != 0
+    }
+
+    pub fn insert(&mut self, scope: Scope) {
+        self.0 |= scope.bit();
+    }
+
+    pub fn toggle(&mut self, scope: Scope) {
+        self.0 ^= scope.bit();
+    }
+
+    pub fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+
+    pub fn iter(self) -> impl Iterator<Item = Scope> {
+        Scope::ALL.into_iter().filter(move |&scope| self.contains(scope))
+    }
+
+    /// Comma-separated labels, e.g. `text, tool input`.
+    pub fn describe(self) -> String {
+        self.iter().map(Scope::label).collect::<Vec<_>>().join(", ")
+    }
+
+    /// Parses a comma-separated list such as `text,tool-input,thinking`.
+    pub fn parse(list: &str) -> Result<Self, String> {
+        let mut scopes = Scopes::NONE;
+        for name in list.split(',').map(str::trim).filter(|name| !name.is_empty()) {
+            let normalized = name.replace(['-', '_'], " ");
+            let scope = Scope::ALL
+                .into_iter()
+                .find(|scope| scope.label() == normalized)
+                .ok_or_else(|| format!("unknown scope: {name}"))?;
+            scopes.insert(scope);
+        }
+        Ok(scopes)
+    }
+}
+
+/// The parts of a message needed to list and search it without the full
+/// record. Extracted once at parse time and kept in the index cache.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MessageText {
+    pub kind: String,
+    /// When the message was written, parsed from its RFC 3339 `timestamp`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub time: Option<DateTime<Utc>>,
+    pub content: Vec<String>,
+    /// Scope of each entry of `content`.
+    #[serde(default)]
+    pub scopes: Vec<Scope>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub session_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub git_branch: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    /// Tools called by the message.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tools: Vec<String>,
+    /// Id of each call in `tools`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tool_ids: Vec<String>,
+    /// Calls whose results in this message are errors.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub failed_tools: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub uuid: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parent_uuid: Option<String>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub is_sidechain: bool,
+    /// Last message of the conversation a summary describes.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub leaf_uuid: Option<String>,
+    /// Tokens of the API response, on the first record of each response only.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tokens: Option<TokenUsage>,
+}
+
+impl MessageText {
+    pub fn from_message(message: &SessionMessage) -> Self {
+        let (scopes, content) = message.get_scoped_content().into_iter().unzip();
+        Self {
+            kind: message.get_type().to_string(),
+            time: message.get_time(),
+            content,
+            scopes,
+            session_id: message.get_session_id().map(str::to_string),
+            git_branch: message.get_git_branch().map(str::to_string),
+            model: message.get_model().map(str::to_string),
+            tools: message.get_tool_names(),
+            tool_ids: message.get_tool_use_ids(),
+            failed_tools: message.get_failed_tool_use_ids(),
+            uuid: message.get_uuid().map(str::to_string),
+            parent_uuid: message.get_parent_uuid().map(str::to_string),
+            is_sidechain: message.is_sidechain(),
+            leaf_uuid: message.get_leaf_uuid().map(str::to_string),
+            tokens: message.get_usage().map(TokenUsage::from_usage),
+        }
+    }
+
+    /// Each entry of `content` with its scope.
+    pub fn sections(&self) -> impl Iterator<Item = (Scope, &str)> {
+        self.content
+            .iter()
+            .enumerate()
+            .map(|(i, text)| (self.scopes.get(i).copied().unwrap_or(Scope::Text), text.as_str()))
+    }
+
+    /// `content` in `scopes`, with anything but plain text labelled, joined
+    /// into one string for display.
+    pub fn display_text(&self, scopes: Scopes) -> String {
+        self.sections()
+            .filter(|(scope, _)| scopes.contains(*scope))
+            .map(|(scope, text)| match scope {
+                Scope::Text => text.to_string(),
+                scope => format!("[{}] {}", scope.label(), text),
+            })
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
+
+/// Token counts of one API response, or the sum of several.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TokenUsage {
+    #[serde(default)]
+    pub input: u64,
+    #[serde(default)]
+    pub output: u64,
+    #[serde(default)]
+    pub cache_creation: u64,
+    #[serde(default)]
+    pub cache_read: u64,
+    #[serde(default)]
+    pub web_search_requests: u64,
+}
+
+impl TokenUsage {
+    pub fn from_usage(usage: &Usage) -> Self {
+        Self {
+            input: usage.input_tokens.unwrap_or(0),
+            output: usage.output_tokens.unwrap_or(0),
+            cache_creation: usage.cache_creation_input_tokens.unwrap_or(0),
+            cache_read: usage.cache_read_input_tokens.unwrap_or(0),
+            web_search_requests: usage
+                .server_tool_use
+                .as_ref()
+                .and_then(|tools| tools.web_search_requests)
+                .unwrap_or(0),
+        }
+    }
+
+    /// Every token read or written, cached or not.
+    pub fn total(&self) -> u64 {
+        self.input + self.output + self.cache_creation + self.cache_read
+    }
+
+    /// Share of the prompt read from the cache rather than sent or written
+    /// to it, `None` when there was no prompt.
+    pub fn cache_hit_ratio(&self) -> Option<f64> {
+        let prompt = self.input + self.cache_creation + self.cache_read;
+        (prompt > 0).then(|| self.cache_read as f64 / prompt as f64)
+    }
+
+    pub fn sum<'a>(usages: impl IntoIterator<Item = &'a TokenUsage>) -> Self {
+        usages.into_iter().fold(Self::default(), |mut sum, usage| {
+            sum += *usage;
+            sum
+        })
+    }
+}
+
+impl std::ops::AddAssign for TokenUsage {
+    fn add_assign(&mut self, other: Self) {
+        self.input += other.input;
+        self.output += other.output;
+        self.cache_creation += other.cache_creation;
+        self.cache_read += other.cache_read;
+        self.web_search_requests += other.web_search_requests;
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct ProjectFile {
-    #[allow(dead_code)]
     pub path: String,
+    /// Name of the folder the session file is in, derived from the project
+    /// path with `/` and `.` replaced by `-`.
     pub project_name: String,
+    /// Directory the session was started in; see [`ProjectFile::resolve_path`].
+    pub project_path: String,
+    /// Repository-relative name for lists, e.g. `repo` or `repo/crates/foo`.
+    pub short_name: String,
+    /// Title of the session; see [`ProjectFile::resolve_title`].
+    pub title: Option<String>,
+    /// Size of the file on disk when it was read.
+    pub size: u64,
+    pub modified: Option<SystemTime>,
+    /// Full records. Empty for files restored from the index cache until
+    /// they are loaded on demand; see [`ProjectFile::is_loaded`].
     pub messages: Vec<SessionMessage>,
+    /// Searchable text of each message, parallel to `messages`.
+    pub texts: Vec<MessageText>,
+    pub diagnostics: Vec<ParseDiagnostic>,
 }
 
 impl ProjectFile {
@@ -249,8 +937,15 @@ impl ProjectFile {
         let project_name = Self::extract_project_name(&path);
         Self {
             path,
+            project_path: String::new(),
+            short_name: project_name.clone(),
             project_name,
+            title: None,
+            size: 0,
+            modified: None,
             messages: Vec::new(),
+            texts: Vec::new(),
+            diagnostics: Vec::new(),
         }
     }
 
@@ -261,4 +956,131 @@ impl ProjectFile {
             .unwrap_or("unknown")
             .to_string()
     }
-}
\ No newline at end of file
+
+    /// Sets the project path to `cwd` (the working directory recorded in
+    /// the session), or to the de-mangled folder name when there is none.
+    pub fn resolve_path(&mut self, cwd: Option<&str>) {
+        self.project_path = match cwd {
+            Some(cwd) if !cwd.is_empty() => cwd.to_string(),
+            _ => paths::demangle(&self.project_name),
+        };
+        self.short_name = paths::short_name(&self.project_path);
+    }
+
+    /// Derives the title from the summaries and prompts in `texts`.
+    pub fn resolve_title(&mut self) {
+        self.title = title::session_title(&self.texts);
+    }
+
+    /// Titles `projects` together, so that a summary written into another
+    /// session's file titles the session it describes.
+    pub fn resolve_titles(projects: &mut [ProjectFile]) {
+        let texts: Vec<&[MessageText]> = projects.iter().map(|project| project.texts.as_slice()).collect();
+        let titles = title::session_titles(&texts);
+        for (project, title) in projects.iter_mut().zip(titles) {
+            project.title = title;
+        }
+    }
+
+    /// The title, or the session id for sessions without one.
+    pub fn label(&self) -> &str {
+        self.title.as_deref().unwrap_or_else(|| self.session_id())
+    }
+
+    pub fn message_count(&self) -> usize {
+        self.texts.len()
+    }
+
+    /// Session id, taken from the file name (`<session id>.jsonl`).
+    pub fn session_id(&self) -> &str {
+        Path::new(&self.path)
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .unwrap_or(&self.path)
+    }
+
+    /// Tokens used by every response in the session.
+    pub fn tokens(&self) -> TokenUsage {
+        TokenUsage::sum(self.texts.iter().filter_map(|text| text.tokens.as_ref()))
+    }
+
+    /// Whether `messages` holds the full records rather than only `texts`.
+    pub fn is_loaded(&self) -> bool {
+        self.messages.len() == self.texts.len()
+    }
+}
+
+// ============================================
+// Search Results
+// ============================================
+
+/// Messages of one project matching a query, referenced by index rather
+/// than cloned so results stay cheap to build and keep around.
+#[derive(Debug, Clone)]
+pub struct SearchResult {
+    pub project: Arc<ProjectFile>,
+    /// Indices into `project.texts` (and `project.messages`), in file order.
+    pub message_indices: Vec<usize>,
+    /// Relevance of each message in `message_indices`. Empty when the result
+    /// was not ranked, e.g. for an empty query.
+    pub scores: Vec<f32>,
+    /// Scopes the search matched in, for each message in `message_indices`.
+    /// Empty when nothing was searched for.
+    pub scopes: Vec<Scopes>,
+}
+
+impl SearchResult {
+    /// A result covering every message of the project.
+    pub fn all(project: Arc<ProjectFile>) -> Self {
+        let message_indices = (0..project.message_count()).collect();
+        Self {
+            project,
+            message_indices,
+            scores: Vec::new(),
+            scopes: Vec::new(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.message_indices.len()
+    }
+
+    /// Score of the best matching message, if the result was ranked.
+    pub fn score(&self) -> Option<f32> {
+        self.scores.iter().copied().reduce(f32::max)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.message_indices.is_empty()
+    }
+
+    pub fn texts(&self) -> impl Iterator<Item = &MessageText> {
+        self.message_indices.iter().map(|&i| &self.project.texts[i])
+    }
+
+    /// The matching texts with their scores and matched scopes, best first.
+    /// Unranked results keep file order.
+    pub fn ranked_texts(&self) -> Vec<(&MessageText, Option<f32>, Option<Scopes>)> {
+        let mut ranked: Vec<_> = self
+            .message_indices
+            .iter()
+            .enumerate()
+            .map(|(i, &message)| {
+                (
+                    &self.project.texts[message],
+                    self.scores.get(i).copied(),
+                    self.scopes.get(i).copied(),
+                )
+            })
+            .collect();
+        ranked.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
+        ranked
+    }
+
+    /// The matching records. Yields nothing until the project is loaded.
+    pub fn messages(&self) -> impl Iterator<Item = &SessionMessage> {
+        self.message_indices
+            .iter()
+            .filter_map(|&i| self.project.messages.get(i))
+    }
+}
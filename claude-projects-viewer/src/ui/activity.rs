@@ -0,0 +1,290 @@
+use crate::stats::ActivityStats;
+use chrono::{DateTime, Datelike, Duration, Local};
+use iocraft::prelude::*;
+use std::sync::Arc;
+
+/// Weeks shown in the calendar heatmap, ending with the current week.
+const HEATMAP_WEEKS: usize = 52;
+/// Rows of the hour-of-day histogram.
+const HISTOGRAM_HEIGHT: usize = 5;
+/// Columns of each session's strip in the timeline.
+const TIMELINE_WIDTH: usize = 60;
+
+const SHADES: [char; 4] = ['░', '▒', '▓', '█'];
+const BLOCKS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+#[derive(Default, Props)]
+pub struct ActivityViewProps {
+    pub stats: Arc<ActivityStats>,
+    /// Sessions shown in the timeline, the most recent ones. `None` shows
+    /// every session (print mode).
+    pub rows: Option<usize>,
+}
+
+/// When the work happened: a calendar heatmap of messages per day, a
+/// histogram of the hours they were written in, and a timeline of sessions.
+#[component]
+pub fn ActivityView(props: &ActivityViewProps) -> impl Into<AnyElement<'static>> {
+    let stats = &props.stats;
+    let busiest = stats.days.iter().max_by_key(|(_, count)| **count);
+
+    element! {
+        View(flex_direction: FlexDirection::Column, width: 100pct) {
+            // Totals
+            View(
+                border_style: BorderStyle::Round,
+                border_color: Color::Green,
+                padding_left: 1,
+                padding_right: 1,
+                margin_bottom: 1,
+            ) {
+                Text(
+                    content: format!(
+                        "Activity - {} messages on {} days in {} sessions{}",
+                        stats.messages,
+                        stats.days.len(),
+                        stats.sessions.len(),
+                        busiest.map_or(String::new(), |(day, count)| format!(" · busiest {} ({} messages)", day, count)),
+                    ),
+                    color: Color::Green,
+                    weight: Weight::Bold,
+                )
+            }
+
+            Heatmap(stats: stats.clone())
+            HourHistogram(hours: stats.hours)
+            Timeline(stats: stats.clone(), rows: props.rows)
+        }
+    }
+}
+
+#[derive(Default, Props)]
+struct HeatmapProps {
+    stats: Arc<ActivityStats>,
+}
+
+/// Messages per day, one column per week and one row per weekday.
+#[component]
+fn Heatmap(props: &HeatmapProps) -> impl Into<AnyElement<'static>> {
+    let today = Local::now().date_naive();
+    let end = props.stats.last_day().map_or(today, |last| last.max(today));
+    let first_monday = end - Duration::days(end.weekday().num_days_from_monday() as i64)
+        - Duration::weeks(HEATMAP_WEEKS as i64 - 1);
+    let day_at = |week: usize, weekday: usize| first_monday + Duration::days((week * 7 + weekday) as i64);
+    let max = (0..HEATMAP_WEEKS * 7)
+        .map(|i| props.stats.on(day_at(i / 7, i % 7)))
+        .max()
+        .unwrap_or(0);
+
+    // 月の変わり目の週に月名を置く
+    let mut months = String::new();
+    let mut last_month = None;
+    for week in 0..HEATMAP_WEEKS {
+        let monday = day_at(week, 0);
+        if months.chars().count() <= week * 2 && last_month != Some(monday.month()) {
+            months.push_str(&format!("{:<width$}", "", width = week * 2 - months.chars().count()));
+            months.push_str(&monday.format("%b").to_string());
+        }
+        last_month = Some(monday.month());
+    }
+
+    element! {
+        View(
+            flex_direction: FlexDirection::Column,
+            border_style: BorderStyle::Round,
+            border_color: Color::DarkGrey,
+            padding_left: 1,
+            padding_right: 1,
+            margin_bottom: 1,
+        ) {
+            Text(content: format!("Messages per day, {} – {}", first_monday, end), weight: Weight::Bold)
+            View {
+                View(width: 4) {
+                    Text(content: "")
+                }
+                Text(content: months, color: Color::Grey)
+            }
+            #((0..7).map(|weekday| element! {
+                View {
+                    View(width: 4) {
+                        Text(
+                            content: match weekday {
+                                0 => "Mon",
+                                2 => "Wed",
+                                4 => "Fri",
+                                _ => "",
+                            },
+                            color: Color::Grey,
+                        )
+                    }
+                    #((0..HEATMAP_WEEKS).map(|week| {
+                        let day = day_at(week, weekday);
+                        let count = props.stats.on(day);
+                        let (cell, color) = match count {
+                            _ if day > end => (' ', Color::Reset),
+                            0 => ('·', Color::DarkGrey),
+                            count => (SHADES[level(count, max, SHADES.len()) - 1], Color::Green),
+                        };
+                        element! {
+                            Text(content: format!("{} ", cell), color: color)
+                        }
+                    }))
+                }
+            }))
+            View(margin_top: 1) {
+                View(width: 4) {
+                    Text(content: "")
+                }
+                Text(content: "Less · ", color: Color::Grey)
+                Text(content: SHADES.iter().map(|shade| format!("{} ", shade)).collect::<String>(), color: Color::Green)
+                Text(content: "More", color: Color::Grey)
+            }
+        }
+    }
+}
+
+#[derive(Default, Props)]
+struct HourHistogramProps {
+    hours: [usize; 24],
+}
+
+/// Messages per hour of the day as vertical bars.
+#[component]
+fn HourHistogram(props: &HourHistogramProps) -> impl Into<AnyElement<'static>> {
+    let max = props.hours.iter().copied().max().unwrap_or(0);
+    let eighths: Vec<usize> = props
+        .hours
+        .iter()
+        .map(|&count| if count == 0 { 0 } else { level(count, max, HISTOGRAM_HEIGHT * 8) })
+        .collect();
+    let rows: Vec<String> = (0..HISTOGRAM_HEIGHT)
+        .rev()
+        .map(|row| {
+            eighths
+                .iter()
+                .map(|&filled| match filled.saturating_sub(row * 8).min(8) {
+                    0 => "   ".to_string(),
+                    part => format!("{0}{0} ", BLOCKS[part - 1]),
+                })
+                .collect()
+        })
+        .collect();
+    let labels: String = (0..24).step_by(3).map(|hour| format!("{:<9}", hour)).collect();
+
+    element! {
+        View(
+            flex_direction: FlexDirection::Column,
+            border_style: BorderStyle::Round,
+            border_color: Color::DarkGrey,
+            padding_left: 1,
+            padding_right: 1,
+            margin_bottom: 1,
+        ) {
+            Text(content: "Messages by hour of day", weight: Weight::Bold)
+            #(rows.into_iter().map(|row| element! {
+                Text(content: row, color: Color::Blue)
+            }))
+            Text(content: labels, color: Color::Grey)
+        }
+    }
+}
+
+#[derive(Default, Props)]
+struct TimelineProps {
+    stats: Arc<ActivityStats>,
+    rows: Option<usize>,
+}
+
+/// The most recent sessions on a shared time axis, each drawn from its
+/// first to its last message.
+#[component]
+fn Timeline(props: &TimelineProps) -> impl Into<AnyElement<'static>> {
+    let sessions = &props.stats.sessions;
+    let shown = &sessions[sessions.len() - props.rows.unwrap_or(sessions.len()).min(sessions.len())..];
+    let from = shown.iter().map(|span| span.start).min();
+    let to = shown.iter().map(|span| span.end).max();
+    let column = |time: DateTime<Local>| match (from, to) {
+        (Some(from), Some(to)) if to > from => {
+            let offset = (time - from).num_seconds() as f64 / (to - from).num_seconds() as f64;
+            ((offset * (TIMELINE_WIDTH - 1) as f64).round() as usize).min(TIMELINE_WIDTH - 1)
+        }
+        _ => 0,
+    };
+
+    element! {
+        View(
+            flex_direction: FlexDirection::Column,
+            border_style: BorderStyle::Round,
+            border_color: Color::DarkGrey,
+            padding_left: 1,
+            padding_right: 1,
+        ) {
+            Text(
+                content: if shown.len() < sessions.len() {
+                    format!("Session timeline (latest {} of {})", shown.len(), sessions.len())
+                } else {
+                    "Session timeline".to_string()
+                },
+                weight: Weight::Bold,
+            )
+            #(shown.iter().map(|span| {
+                let (start, end) = (column(span.start), column(span.end));
+                element! {
+                    View {
+                        View(width: 30, height: 1, overflow: Overflow::Hidden) {
+                            Text(content: &span.title)
+                        }
+                        Text(content: "·".repeat(start), color: Color::DarkGrey)
+                        Text(
+                            content: if start == end { "●".to_string() } else { "━".repeat(end - start + 1) },
+                            color: Color::Cyan,
+                        )
+                        Text(content: "·".repeat(TIMELINE_WIDTH - 1 - end), color: Color::DarkGrey)
+                        Text(
+                            content: format!(
+                                " {} · {} · {} msgs",
+                                span.start.format("%m-%d %H:%M"),
+                                format_duration(span.end - span.start),
+                                span.messages,
+                            ),
+                            color: Color::Grey,
+                        )
+                    }
+                }
+            }))
+            #(from.zip(to).map(|(from, to)| element! {
+                View {
+                    View(width: 30) {
+                        Text(content: "")
+                    }
+                    View(width: TIMELINE_WIDTH as u16) {
+                        Text(
+                            content: format!(
+                                "{:<width$}{}",
+                                from.format("%Y-%m-%d %H:%M"),
+                                to.format("%Y-%m-%d %H:%M"),
+                                width = TIMELINE_WIDTH - 16,
+                            ),
+                            color: Color::Grey,
+                        )
+                    }
+                }
+            }))
+        }
+    }
+}
+
+/// `count` on a scale of 1 to `levels`, relative to `max`.
+fn level(count: usize, max: usize, levels: usize) -> usize {
+    (count * levels).div_ceil(max.max(1)).clamp(1, levels)
+}
+
+/// `45m`, `3h 20m` or `2d 4h`.
+fn format_duration(duration: Duration) -> String {
+    let minutes = duration.num_minutes();
+    match minutes {
+        0..60 => format!("{}m", minutes),
+        60..1440 => format!("{}h {}m", minutes / 60, minutes % 60),
+        _ => format!("{}d {}h", minutes / 1440, minutes % 1440 / 60),
+    }
+}
@@ -0,0 +1,80 @@
+use crate::types::ParseDiagnostic;
+use iocraft::prelude::*;
+
+#[derive(Default, Props)]
+pub struct DiagnosticsViewProps {
+    pub diagnostics: Vec<ParseDiagnostic>,
+    /// Height of the scrollable list. `None` renders every row (print mode).
+    pub height: Option<u16>,
+}
+
+/// Lists the lines that could not be parsed, grouped by file.
+#[component]
+pub fn DiagnosticsView(props: &DiagnosticsViewProps) -> impl Into<AnyElement<'static>> {
+    let file_count = {
+        let mut paths: Vec<&str> = props.diagnostics.iter().map(|d| d.path.as_str()).collect();
+        paths.dedup();
+        paths.len()
+    };
+
+    element! {
+        View(flex_direction: FlexDirection::Column, width: 100pct) {
+            // Header
+            View(
+                border_style: BorderStyle::Round,
+                border_color: Color::Yellow,
+                padding: 1,
+                margin_bottom: 1,
+            ) {
+                Text(
+                    content: if props.diagnostics.is_empty() {
+                        "Parse diagnostics - every line parsed successfully".to_string()
+                    } else {
+                        format!(
+                            "Parse diagnostics - {} unparsed lines in {} files",
+                            props.diagnostics.len(),
+                            file_count,
+                        )
+                    },
+                    color: Color::Yellow,
+                    weight: Weight::Bold,
+                )
+            }
+
+            View(
+                flex_direction: FlexDirection::Column,
+                height: props.height.map(Size::from).unwrap_or(Size::Auto),
+                overflow: if props.height.is_some() { Overflow::Scroll } else { Overflow::Visible },
+            ) {
+                #(props.diagnostics.iter().map(|diagnostic| {
+                    element! {
+                        View(
+                            flex_direction: FlexDirection::Column,
+                            border_style: BorderStyle::Single,
+                            border_color: Color::DarkGrey,
+                            padding_left: 1,
+                            padding_right: 1,
+                        ) {
+                            View(flex_direction: FlexDirection::Row) {
+                                Text(
+                                    content: format!("{}:{}", diagnostic.path, diagnostic.line),
+                                    color: Color::White,
+                                    weight: Weight::Bold,
+                                )
+                                Text(
+                                    content: format!(
+                                        " (byte {}, type: {})",
+                                        diagnostic.byte_offset,
+                                        diagnostic.record_type.as_deref().unwrap_or("-"),
+                                    ),
+                                    color: Color::DarkGrey,
+                                )
+                            }
+                            Text(content: &diagnostic.error, color: Color::Red, wrap: TextWrap::Wrap)
+                        }
+                    }
+                }))
+            }
+        }
+    }
+}
@@ -0,0 +1,47 @@
+use iocraft::prelude::*;
+use std::ops::Range;
+
+#[derive(Default, Props)]
+pub struct HighlightedTextProps {
+    pub content: String,
+    /// Byte ranges of `content` to emphasize, sorted and not overlapping.
+    pub highlights: Vec<Range<usize>>,
+    pub color: Option<Color>,
+    pub wrap: TextWrap,
+}
+
+/// Text with the parts matched by a search emphasized.
+#[component]
+pub fn HighlightedText(props: &HighlightedTextProps) -> impl Into<AnyElement<'static>> {
+    let mut contents = Vec::new();
+    let plain = |text: &str, contents: &mut Vec<MixedTextContent>| {
+        if !text.is_empty() {
+            let content = MixedTextContent::new(text);
+            contents.push(match props.color {
+                Some(color) => content.color(color),
+                None => content,
+            });
+        }
+    };
+
+    let mut at = 0;
+    for range in &props.highlights {
+        let (start, end) = (range.start.max(at), range.end.min(props.content.len()));
+        if start >= end || !props.content.is_char_boundary(start) || !props.content.is_char_boundary(end) {
+            continue;
+        }
+        plain(&props.content[at..start], &mut contents);
+        contents.push(
+            MixedTextContent::new(&props.content[start..end])
+                .color(Color::Yellow)
+                .weight(Weight::Bold)
+                .decoration(TextDecoration::Underline),
+        );
+        at = end;
+    }
+    plain(&props.content[at..], &mut contents);
+
+    element! {
+        MixedText(contents, wrap: props.wrap)
+    }
+}
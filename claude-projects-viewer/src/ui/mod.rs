@@ -0,0 +1,73 @@
+//! Components shared by the viewer binaries.
+
+mod activity;
+mod diagnostics;
+mod highlight;
+mod report;
+mod sessions;
+mod thread;
+mod tools;
+mod usage;
+
+pub use activity::ActivityView;
+pub use diagnostics::DiagnosticsView;
+pub use highlight::HighlightedText;
+pub use report::{ResultsReport, SessionReport};
+pub use sessions::SessionsView;
+pub use thread::ThreadView;
+pub use tools::ToolsView;
+pub use usage::UsageView;
+
+use chrono::{DateTime, Local, Utc};
+use std::ops::Range;
+use std::time::SystemTime;
+
+/// Rows to render when only `capacity` of `len` rows fit on screen, scrolled
+/// so that `selected` stays visible.
+pub fn visible_range(selected: usize, len: usize, capacity: usize) -> Range<usize> {
+    let capacity = capacity.max(1);
+    let start = (selected + 1).saturating_sub(capacity).min(len.saturating_sub(capacity));
+    start..(start + capacity).min(len)
+}
+
+/// Token counts shortened for narrow columns, e.g. `950`, `12.3k`, `4.5M`.
+pub fn format_tokens(count: u64) -> String {
+    match count {
+        0..1_000 => count.to_string(),
+        1_000..1_000_000 => format!("{:.1}k", count as f64 / 1_000.0),
+        _ => format!("{:.1}M", count as f64 / 1_000_000.0),
+    }
+}
+
+/// Local date and time to the minute, or `-` when unknown.
+pub fn format_time(time: Option<SystemTime>) -> String {
+    time.map_or("-".to_string(), |time| DateTime::<Local>::from(time).format("%Y-%m-%d %H:%M").to_string())
+}
+
+/// A message's time in local time to the second, or `N/A`.
+pub fn format_datetime(time: Option<DateTime<Utc>>) -> String {
+    time.map_or("N/A".to_string(), |time| time.with_timezone(&Local).format("%Y-%m-%d %H:%M:%S").to_string())
+}
+
+/// One block character per value, scaled to the largest; zero is a space.
+pub fn sparkline(values: &[u64]) -> String {
+    const BLOCKS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+    let max = values.iter().copied().max().unwrap_or(0).max(1);
+    values
+        .iter()
+        .map(|&value| match value {
+            0 => ' ',
+            value => BLOCKS[((value * 8).div_ceil(max) as usize).clamp(1, 8) - 1],
+        })
+        .collect()
+}
+
+/// Dollars with cents, or to a hundredth of a cent below a dollar.
+pub fn format_cost(dollars: f64) -> String {
+    if dollars < 1.0 { format!("${:.4}", dollars) } else { format!("${:.2}", dollars) }
+}
+
+/// A ratio as a percentage with one decimal, or `-`.
+pub fn format_percent(ratio: Option<f64>) -> String {
+    ratio.map_or("-".to_string(), |ratio| format!("{:.1}%", ratio * 100.0))
+}
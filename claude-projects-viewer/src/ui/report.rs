@@ -0,0 +1,326 @@
+use crate::group::ProjectGroup;
+use crate::matcher::Matcher;
+use crate::paths::display_path;
+use crate::pricing::PricingTable;
+use crate::snippet::{message_snippet, result_preview};
+use crate::types::{Scope, Scopes, SearchResult};
+use crate::ui::{format_cost, format_datetime, format_time, format_tokens, HighlightedText};
+use iocraft::prelude::*;
+use std::sync::Arc;
+
+#[derive(Default, Props)]
+pub struct ResultsReportProps {
+    pub results: Arc<Vec<SearchResult>>,
+    /// The search the results are for, empty when everything is listed.
+    pub query: String,
+    pub matcher: Arc<Matcher>,
+    pub scopes: Scopes,
+    pub pricing: Arc<PricingTable>,
+}
+
+/// Every project of a search with its sessions, their previews and the
+/// first few matches, for printing to stdout.
+#[component]
+pub fn ResultsReport(props: &ResultsReportProps) -> impl Into<AnyElement<'static>> {
+    let search_results = &props.results;
+    let query = props.query.as_str();
+    let matcher = &props.matcher;
+    let scopes = props.scopes;
+    let pricing = &props.pricing;
+    let groups = ProjectGroup::group(search_results);
+
+    element! {
+        View(flex_direction: FlexDirection::Column) {
+            #(if search_results.is_empty() {
+                element! {
+                    View(padding: 2) {
+                        Text(
+                            content: if query.is_empty() {
+                                "No projects found"
+                            } else {
+                                "No matching projects found"
+                            },
+                            color: Color::Yellow,
+                            align: TextAlign::Center,
+                        )
+                    }
+                }
+            } else {
+                element! {
+                    View(flex_direction: FlexDirection::Column) {
+                        // Summary
+                        View(margin_bottom: 1) {
+                            Text(
+                                content: if query.is_empty() {
+                                    format!("Showing all {} projects ({} sessions)", groups.len(), search_results.len())
+                                } else {
+                                    format!("Found {} matching projects ({} sessions)", groups.len(), search_results.len())
+                                },
+                                color: Color::Cyan,
+                            )
+                        }
+                        
+                        // Results list
+                        View(flex_direction: FlexDirection::Column) {
+                            #(groups.iter().enumerate().map(|(i, group)| {
+                                let message_count = group.message_count(search_results);
+                                let diagnostic_count = group.diagnostic_count(search_results);
+                                
+                                element! {
+                                    View(
+                                        border_style: BorderStyle::Single,
+                                        border_color: Color::DarkGrey,
+                                        padding: 1,
+                                        margin_bottom: 1,
+                                    ) {
+                                        View(flex_direction: FlexDirection::Column) {
+                                            // Project name with session and message counts
+                                            View(flex_direction: FlexDirection::Row) {
+                                                Text(
+                                                    content: format!("{}. ", i + 1),
+                                                    color: Color::DarkGrey,
+                                                )
+                                                Text(
+                                                    content: &group.name,
+                                                    color: Color::White,
+                                                    weight: Weight::Bold,
+                                                )
+                                                Text(
+                                                    content: match group.score(search_results) {
+                                                        Some(score) => format!(" ({} sessions, {} matches, score {:.1})", group.sessions.len(), message_count, score),
+                                                        None => format!(
+                                                            " ({} sessions, {} {})",
+                                                            group.sessions.len(),
+                                                            message_count,
+                                                            if query.is_empty() { "messages" } else { "matches" }
+                                                        ),
+                                                    },
+                                                    color: Color::Cyan,
+                                                )
+                                                #(if diagnostic_count > 0 {
+                                                    element! {
+                                                        Text(
+                                                            content: format!(" ⚠ {} unparsed", diagnostic_count),
+                                                            color: Color::Yellow,
+                                                        )
+                                                    }
+                                                } else {
+                                                    element! { Text(content: "") }
+                                                })
+                                            }
+                                            Text(
+                                                content: format!(
+                                                    "   {} · last activity {} · {} tokens",
+                                                    display_path(&group.path),
+                                                    format_time(group.last_activity(search_results)),
+                                                    format_tokens(group.tokens(search_results).total()),
+                                                ),
+                                                color: Color::Grey,
+                                            )
+                                            // Sessions
+                                            #(group.results(search_results).map(|result| {
+                                                let total_messages = result.len();
+                                                let preview = result_preview(result, matcher, scopes, 60);
+                                                let cost = pricing.session_cost(&result.project);
+                                                
+                                                element! {
+                                                    View(flex_direction: FlexDirection::Column, margin_top: 1, padding_left: 2) {
+                                                        View(flex_direction: FlexDirection::Row) {
+                                                            Text(
+                                                                content: format!("▸ {}", result.project.label()),
+                                                                color: Color::White,
+                                                                weight: Weight::Bold,
+                                                            )
+                                                            Text(
+                                                                content: match result.score() {
+                                                                    Some(score) => format!(" ({} matches, score {:.1})", total_messages, score),
+                                                                    None => format!(
+                                                                        " ({} {})",
+                                                                        total_messages,
+                                                                        if query.is_empty() { "messages" } else { "matches" }
+                                                                    ),
+                                                                },
+                                                                color: Color::Cyan,
+                                                            )
+                                                            Text(
+                                                                content: format!(
+                                                                    " · {} · {} · {} tokens",
+                                                                    result.project.session_id(),
+                                                                    format_time(result.project.modified),
+                                                                    format_tokens(result.project.tokens().total()),
+                                                                ),
+                                                                color: Color::Grey,
+                                                            )
+                                                            Text(
+                                                                content: format!(" · {}", format_cost(cost)),
+                                                                color: if pricing.session_over_budget(cost) { Color::Red } else { Color::Yellow },
+                                                            )
+                                                        }
+                                                        // Preview
+                                                        View(padding_left: 2) {
+                                                            HighlightedText(
+                                                                content: preview.text,
+                                                                highlights: preview.highlights,
+                                                                color: Color::DarkGrey,
+                                                            )
+                                                        }
+                                                        // Show first few matches if searching
+                                                        #(if !query.is_empty() && !result.is_empty() {
+                                                            element! {
+                                                                View(margin_top: 1, padding_left: 2) {
+                                                                    View(flex_direction: FlexDirection::Column) {
+                                                                        #(result.ranked_texts().into_iter().take(3).enumerate().map(|(j, (msg, score, matched_in))| {
+                                                                            let msg_type = msg.kind.as_str();
+                                                                            let label = match (score, matched_in) {
+                                                                                (Some(score), Some(scopes)) => format!("{} {:.1} · {}", msg_type, score, scopes.describe()),
+                                                                                (Some(score), None) => format!("{} {:.1}", msg_type, score),
+                                                                                _ => msg_type.to_string(),
+                                                                            };
+                                                                            let content = message_snippet(msg, matcher, scopes, 40, 2);
+                                                                            let color = match msg_type {
+                                                                                "user" => Color::Green,
+                                                                                "assistant" => Color::Blue,
+                                                                                "system" => Color::Yellow,
+                                                                                _ => Color::Grey,
+                                                                            };
+                                                                
+                                                                            element! {
+                                                                                View(margin_bottom: if j < 2 { 1 } else { 0 }) {
+                                                                                    Text(
+                                                                                        content: format!("• [{}] ", label),
+                                                                                        color: color,
+                                                                                    )
+                                                                                    HighlightedText(
+                                                                                        content: content.text,
+                                                                                        highlights: content.highlights,
+                                                                                        color: color,
+                                                                                    )
+                                                                                }
+                                                                            }
+                                                                        }))
+                                                                        #(if result.len() > 3 {
+                                                                            element! {
+                                                                                View(margin_top: 1) {
+                                                                                    Text(
+                                                                                        content: format!("  ... and {} more matches", result.len() - 3),
+                                                                                        color: Color::DarkGrey,
+                                                                                    )
+                                                                                }
+                                                                            }
+                                                                        } else {
+                                                                            element! { View {} }
+                                                                        })
+                                                                    }
+                                                                }
+                                                            }
+                                                        } else {
+                                                            element! { View {} }
+                                                        })
+                                                    }
+                                                }
+                                            }))
+                                        }
+                                    }
+                                }
+                            }))
+                        }
+                    }
+                }
+            })
+        }
+    }
+}
+
+#[derive(Default, Props)]
+pub struct SessionReportProps {
+    pub result: Option<SearchResult>,
+    pub matcher: Arc<Matcher>,
+    pub scopes: Scopes,
+    pub pricing: Arc<PricingTable>,
+}
+
+/// Every message of a search result in full, with matches highlighted, for
+/// printing to stdout.
+#[component]
+pub fn SessionReport(props: &SessionReportProps) -> impl Into<AnyElement<'static>> {
+    let Some(result) = &props.result else {
+        return element! {
+            View {
+                Text(content: "Result not found", color: Color::Red)
+            }
+        };
+    };
+    let project = &result.project;
+    let cost = props.pricing.session_cost(project);
+
+    element! {
+        View(flex_direction: FlexDirection::Column) {
+            // Header
+            View(
+                border_style: BorderStyle::Round,
+                border_color: Color::Blue,
+                padding_left: 1,
+                padding_right: 1,
+                margin_bottom: 1,
+                flex_direction: FlexDirection::Column,
+            ) {
+                View {
+                    Text(content: &project.short_name, color: Color::White, weight: Weight::Bold)
+                    Text(content: format!(" · {}", project.label()), color: Color::Grey)
+                }
+                Text(
+                    content: format!(
+                        "{} messages in session {} · {} · {} tokens · {}",
+                        result.len(),
+                        project.session_id(),
+                        display_path(&project.project_path),
+                        format_tokens(project.tokens().total()),
+                        format_cost(cost),
+                    ),
+                    color: Color::Cyan,
+                )
+            }
+
+            #(result.texts().enumerate().map(|(i, msg)| {
+                let color = match msg.kind.as_str() {
+                    "user" => Color::Green,
+                    "assistant" => Color::Blue,
+                    "system" => Color::Yellow,
+                    _ => Color::Grey,
+                };
+                element! {
+                    View(flex_direction: FlexDirection::Column, margin_bottom: 1) {
+                        View {
+                            Text(content: format!("[{}] {}", i + 1, msg.kind), color, weight: Weight::Bold)
+                            Text(content: format!(" - {}", format_datetime(msg.time)), color: Color::DarkGrey)
+                            #(msg.tokens.map(|tokens| element! {
+                                Text(
+                                    content: match props.pricing.message_cost(msg) {
+                                        Some(cost) => format!(" · {} tok · {}", format_tokens(tokens.total()), format_cost(cost)),
+                                        None => format!(" · {} tok", format_tokens(tokens.total())),
+                                    },
+                                    color: Color::Yellow,
+                                )
+                            }))
+                        }
+                        #(msg.sections().filter(|(scope, _)| props.scopes.contains(*scope)).map(|(scope, content)| {
+                            // テキスト以外はスコープ名を付け、ハイライト位置をその分ずらす
+                            let label = if scope == Scope::Text { String::new() } else { format!("[{}] ", scope.label()) };
+                            let highlights: Vec<_> = props
+                                .matcher
+                                .find(content)
+                                .into_iter()
+                                .map(|range| range.start + label.len()..range.end + label.len())
+                                .collect();
+                            element! {
+                                View(padding_left: 2) {
+                                    HighlightedText(content: label + content, highlights, wrap: TextWrap::Wrap)
+                                }
+                            }
+                        }))
+                    }
+                }
+            }))
+        }
+    }
+}
@@ -0,0 +1,150 @@
+use crate::matcher::Matcher;
+use crate::paths::display_path;
+use crate::pricing::PricingTable;
+use crate::snippet::result_preview;
+use crate::types::{Scopes, SearchResult};
+use crate::ui::{format_cost, format_time, format_tokens, visible_range, HighlightedText};
+use iocraft::prelude::*;
+use std::sync::Arc;
+
+#[derive(Default, Props)]
+pub struct SessionsViewProps {
+    /// Short name of the project the sessions belong to.
+    pub project: String,
+    /// Directory of the project.
+    pub path: String,
+    /// The project's sessions that matched the search.
+    pub sessions: Vec<SearchResult>,
+    pub selected: usize,
+    pub matcher: Arc<Matcher>,
+    pub scopes: Scopes,
+    pub pricing: Arc<PricingTable>,
+    pub height: u16,
+}
+
+/// The sessions of one project, one row each.
+#[component]
+pub fn SessionsView(props: &SessionsViewProps) -> impl Into<AnyElement<'static>> {
+    let visible = visible_range(props.selected, props.sessions.len(), (props.height.saturating_sub(14) / 3) as usize);
+
+    element! {
+        View(flex_direction: FlexDirection::Column, width: 100pct) {
+            // Header
+            View(
+                border_style: BorderStyle::Round,
+                border_color: Color::Blue,
+                padding: 1,
+                margin_bottom: 1,
+            ) {
+                View(flex_direction: FlexDirection::Column) {
+                    View(margin_bottom: 1) {
+                        Text(content: &props.project, color: Color::White, weight: Weight::Bold)
+                        Text(content: format!(" · {}", display_path(&props.path)), color: Color::Grey)
+                    }
+                    Text(
+                        content: format!(
+                            "{} sessions · {} messages",
+                            props.sessions.len(),
+                            props.sessions.iter().map(SearchResult::len).sum::<usize>(),
+                        ),
+                        color: Color::Cyan,
+                    )
+                }
+            }
+
+            // Sessions
+            View(
+                flex_direction: FlexDirection::Column,
+                border_style: BorderStyle::Round,
+                border_color: Color::Cyan,
+                width: 100pct,
+            ) {
+                View(
+                    border_style: BorderStyle::Single,
+                    border_edges: Edges::Bottom,
+                    border_color: Color::Grey,
+                    padding_left: 1,
+                    padding_right: 1,
+                ) {
+                    View(width: 35pct) {
+                        Text(content: "Title", weight: Weight::Bold, decoration: TextDecoration::Underline)
+                    }
+                    View(width: 10pct) {
+                        Text(content: "Messages", weight: Weight::Bold, decoration: TextDecoration::Underline)
+                    }
+                    View(width: 25pct) {
+                        Text(content: "Last activity", weight: Weight::Bold, decoration: TextDecoration::Underline)
+                    }
+                    View(width: 10pct) {
+                        Text(content: "Cost", weight: Weight::Bold, decoration: TextDecoration::Underline)
+                    }
+                    View(width: 20pct) {
+                        Text(content: "Preview", weight: Weight::Bold, decoration: TextDecoration::Underline)
+                    }
+                }
+                #(props.sessions.iter().enumerate().skip(visible.start).take(visible.len()).map(|(i, session)| {
+                    let is_selected = i == props.selected;
+                    let preview = result_preview(session, &props.matcher, props.scopes, 20);
+                    let cost = props.pricing.session_cost(&session.project);
+                    let over_budget = props.pricing.session_over_budget(cost);
+
+                    element! {
+                        View(
+                            padding_left: 1,
+                            padding_right: 1,
+                            background_color: if is_selected { Color::DarkGrey } else if i % 2 == 0 { Color::Reset } else { Color::Black },
+                            border_style: if is_selected { BorderStyle::Round } else { BorderStyle::None },
+                            border_color: if is_selected { Color::Green } else { Color::Reset },
+                        ) {
+                            View(width: 35pct) {
+                                Text(
+                                    content: session.project.label(),
+                                    color: if is_selected { Color::White } else { Color::Reset },
+                                    weight: if is_selected { Weight::Bold } else { Weight::Normal },
+                                )
+                            }
+                            View(width: 10pct) {
+                                Text(
+                                    content: match session.score() {
+                                        Some(score) => format!("{} (score {:.1})", session.len(), score),
+                                        None => format!("{}", session.len()),
+                                    },
+                                    color: Color::Cyan,
+                                )
+                            }
+                            View(width: 25pct) {
+                                Text(
+                                    content: format!(
+                                        "{} · {} tok",
+                                        format_time(session.project.modified),
+                                        format_tokens(session.project.tokens().total()),
+                                    ),
+                                    color: Color::Grey,
+                                )
+                            }
+                            View(width: 10pct) {
+                                Text(
+                                    content: format_cost(cost),
+                                    color: if over_budget { Color::Red } else { Color::Yellow },
+                                    weight: if over_budget { Weight::Bold } else { Weight::Normal },
+                                )
+                            }
+                            View(width: 20pct) {
+                                HighlightedText(
+                                    content: preview.text,
+                                    highlights: preview.highlights,
+                                    color: Color::DarkGrey,
+                                )
+                            }
+                        }
+                    }
+                }))
+            }
+
+            // Footer
+            View(margin_top: 1) {
+                Text(content: "↑/↓: navigate | Enter: view messages | ESC: back to projects", color: Color::Grey)
+            }
+        }
+    }
+}
@@ -0,0 +1,139 @@
+use crate::matcher::Matcher;
+use crate::snippet::message_snippet;
+use crate::thread::Thread;
+use crate::types::{ProjectFile, Scopes};
+use crate::paths::display_path;
+use crate::ui::{format_datetime, visible_range, HighlightedText};
+use iocraft::prelude::*;
+use std::sync::Arc;
+
+#[derive(Default, Props)]
+pub struct ThreadViewProps {
+    pub project: Option<Arc<ProjectFile>>,
+    pub thread: Arc<Thread>,
+    /// Messages of the branch being shown, root first.
+    pub branch: Vec<usize>,
+    /// Position of the selected message in `branch`.
+    pub cursor: usize,
+    /// Messages matching the current search, marked in the list.
+    pub matches: Vec<usize>,
+    pub matcher: Arc<Matcher>,
+    pub scopes: Scopes,
+    pub height: u16,
+}
+
+/// One branch of a session's conversation tree, with the messages where the
+/// conversation forked marked so another continuation can be picked.
+#[component]
+pub fn ThreadView(props: &ThreadViewProps) -> impl Into<AnyElement<'static>> {
+    let Some(project) = &props.project else {
+        return element! {
+            View {
+                Text(content: "Result not found", color: Color::Red)
+            }
+        };
+    };
+    let leaves = props.thread.leaves();
+    let branch_number = props
+        .branch
+        .last()
+        .and_then(|leaf| leaves.iter().position(|l| l == leaf))
+        .map_or("-".to_string(), |at| (at + 1).to_string());
+    let visible = visible_range(props.cursor, props.branch.len(), (props.height.saturating_sub(16) / 4) as usize);
+
+    element! {
+        View(flex_direction: FlexDirection::Column, width: 100pct) {
+            // Header
+            View(
+                border_style: BorderStyle::Round,
+                border_color: Color::Magenta,
+                padding: 1,
+                margin_bottom: 1,
+            ) {
+                View(flex_direction: FlexDirection::Column) {
+                    View(margin_bottom: 1) {
+                        Text(content: &project.short_name, color: Color::White, weight: Weight::Bold)
+                        Text(content: format!(" · {}", display_path(&project.project_path)), color: Color::Grey)
+                    }
+                    Text(
+                        content: format!(
+                            "Branch {} of {} · {} messages · {} forks · {} sub-agent messages",
+                            branch_number,
+                            leaves.len(),
+                            props.branch.len(),
+                            props.thread.forks().len(),
+                            props.thread.sidechain_count(),
+                        ),
+                        color: Color::Cyan,
+                    )
+                }
+            }
+
+            // Messages on the branch
+            View(
+                flex_direction: FlexDirection::Column,
+                height: props.height.saturating_sub(16),
+                overflow: Overflow::Hidden,
+            ) {
+                #(props.branch.iter().enumerate().skip(visible.start).take(visible.len()).map(|(position, &message)| {
+                    let text = &project.texts[message];
+                    let msg_type = text.kind.as_str();
+                    let is_selected = position == props.cursor;
+                    let alternatives = props.thread.alternatives(message);
+                    let fork = (alternatives.len() > 1).then(|| {
+                        let at = alternatives.iter().position(|&m| m == message).unwrap_or(0);
+                        format!(" ⑂ {}/{}", at + 1, alternatives.len())
+                    });
+                    let preview = message_snippet(text, &props.matcher, props.scopes, 80, 2);
+                    let color = match msg_type {
+                        "user" => Color::Green,
+                        "assistant" => Color::Blue,
+                        "system" => Color::Yellow,
+                        _ => Color::Grey,
+                    };
+
+                    element! {
+                        View(
+                            flex_direction: FlexDirection::Column,
+                            border_style: if is_selected { BorderStyle::Round } else { BorderStyle::Single },
+                            border_color: if is_selected { Color::Green } else { Color::DarkGrey },
+                            padding_left: 1,
+                            padding_right: 1,
+                        ) {
+                            View {
+                                Text(
+                                    content: format!("{:>3} {}", position + 1, msg_type),
+                                    color: color,
+                                    weight: Weight::Bold,
+                                )
+                                Text(
+                                    content: format!(" - {}", format_datetime(text.time)),
+                                    color: Color::DarkGrey,
+                                )
+                                #(fork.map(|fork| element! {
+                                    Text(content: fork, color: Color::Magenta, weight: Weight::Bold)
+                                }))
+                                #(props.matches.contains(&message).then(|| element! {
+                                    Text(content: " · match", color: Color::Yellow)
+                                }))
+                            }
+                            HighlightedText(
+                                content: preview.text,
+                                highlights: preview.highlights,
+                                wrap: TextWrap::Wrap,
+                            )
+                        }
+                    }
+                }))
+            }
+
+            // Footer
+            View(margin_top: 1) {
+                Text(
+                    content: "↑/↓: select message | ←/→: switch between continuations (⑂) | t: matching messages | ESC: back",
+                    color: Color::Grey,
+                )
+            }
+        }
+    }
+}
@@ -0,0 +1,130 @@
+use crate::stats::{ToolRow, ToolStats};
+use crate::ui::format_percent;
+use iocraft::prelude::*;
+use std::sync::Arc;
+
+/// Entries of a row's breakdown listed before the rest are counted.
+const BREAKDOWN_ENTRIES: usize = 4;
+
+#[derive(Default, Props)]
+pub struct ToolsViewProps {
+    pub stats: Arc<ToolStats>,
+    /// Rows shown per table. `None` shows every row (print mode).
+    pub rows: Option<usize>,
+}
+
+/// Which tools are called, how often they fail, and where.
+#[component]
+pub fn ToolsView(props: &ToolsViewProps) -> impl Into<AnyElement<'static>> {
+    let stats = &props.stats;
+
+    element! {
+        View(flex_direction: FlexDirection::Column, width: 100pct) {
+            // Totals
+            View(
+                border_style: BorderStyle::Round,
+                border_color: Color::Magenta,
+                padding_left: 1,
+                padding_right: 1,
+                margin_bottom: 1,
+            ) {
+                Text(
+                    content: format!(
+                        "Tool usage - {} calls of {} tools in {} projects · {} failed ({})",
+                        stats.calls,
+                        stats.tools.len(),
+                        stats.projects.len(),
+                        stats.failures,
+                        format_percent(stats.failure_rate()),
+                    ),
+                    color: Color::Magenta,
+                    weight: Weight::Bold,
+                )
+            }
+
+            ToolTable(title: "Tools", breakdown: "Projects", rows: stats.tools.clone(), limit: props.rows)
+            ToolTable(title: "Projects", breakdown: "Tools", rows: stats.projects.clone(), limit: props.rows)
+        }
+    }
+}
+
+#[derive(Default, Props)]
+struct ToolTableProps {
+    title: String,
+    /// Heading of the breakdown column.
+    breakdown: String,
+    rows: Vec<ToolRow>,
+    limit: Option<usize>,
+}
+
+#[component]
+fn ToolTable(props: &ToolTableProps) -> impl Into<AnyElement<'static>> {
+    let shown = props.limit.unwrap_or(props.rows.len()).min(props.rows.len());
+
+    element! {
+        View(
+            flex_direction: FlexDirection::Column,
+            border_style: BorderStyle::Round,
+            border_color: Color::Grey,
+            padding_left: 1,
+            padding_right: 1,
+        ) {
+            View(border_style: BorderStyle::Single, border_edges: Edges::Bottom, border_color: Color::DarkGrey) {
+                View(width: 24) {
+                    Text(
+                        content: if shown < props.rows.len() {
+                            format!("{} (top {} of {})", props.title, shown, props.rows.len())
+                        } else {
+                            props.title.clone()
+                        },
+                        weight: Weight::Bold,
+                    )
+                }
+                View(width: 8) {
+                    Text(content: "Calls", weight: Weight::Bold)
+                }
+                View(width: 8) {
+                    Text(content: "Failed", weight: Weight::Bold)
+                }
+                View(width: 8) {
+                    Text(content: "Rate", weight: Weight::Bold)
+                }
+                Text(content: &props.breakdown, weight: Weight::Bold)
+            }
+            #(props.rows.iter().take(shown).map(|row| {
+                let failing = row.failures > 0;
+                element! {
+                    View {
+                        View(width: 24, height: 1, overflow: Overflow::Hidden) {
+                            Text(content: &row.name)
+                        }
+                        View(width: 8) {
+                            Text(content: row.calls.to_string(), color: Color::Cyan)
+                        }
+                        View(width: 8) {
+                            Text(content: row.failures.to_string(), color: if failing { Color::Red } else { Color::Grey })
+                        }
+                        View(width: 8) {
+                            Text(content: format_percent(row.failure_rate()), color: if failing { Color::Red } else { Color::Grey })
+                        }
+                        Text(content: describe_breakdown(row), color: Color::Grey)
+                    }
+                }
+            }))
+        }
+    }
+}
+
+/// `repo 12 · other 3 (+2 more)`.
+fn describe_breakdown(row: &ToolRow) -> String {
+    let listed: Vec<String> = row
+        .breakdown
+        .iter()
+        .take(BREAKDOWN_ENTRIES)
+        .map(|entry| format!("{} {}", entry.name, entry.calls))
+        .collect();
+    match row.breakdown.len().saturating_sub(BREAKDOWN_ENTRIES) {
+        0 => listed.join(" · "),
+        rest => format!("{} (+{} more)", listed.join(" · "), rest),
+    }
+}
@@ -0,0 +1,200 @@
+use crate::stats::{UsageRow, UsageStats};
+use crate::ui::{format_cost, format_percent, format_tokens, sparkline};
+use iocraft::prelude::*;
+use std::sync::Arc;
+
+/// Days covered by the daily sparkline.
+const SPARKLINE_DAYS: usize = 60;
+
+#[derive(Default, Props)]
+pub struct UsageViewProps {
+    pub stats: Arc<UsageStats>,
+    /// Rows shown per breakdown. `None` shows every row (print mode).
+    pub rows: Option<usize>,
+}
+
+/// Token usage and cost totals, a sparkline of daily volume and the
+/// heaviest sessions, projects, models, days and months. Sessions and days
+/// over budget are shown in red.
+#[component]
+pub fn UsageView(props: &UsageViewProps) -> impl Into<AnyElement<'static>> {
+    let stats = &props.stats;
+    let total = stats.total;
+    let volume = stats.daily_volume(SPARKLINE_DAYS);
+    let peak = volume.iter().max_by_key(|(_, tokens)| *tokens).filter(|(_, tokens)| *tokens > 0);
+    let values: Vec<u64> = volume.iter().map(|(_, tokens)| *tokens).collect();
+    let budget = stats.budget;
+    let over_budget = stats.sessions_over_budget() + stats.days_over_budget();
+
+    element! {
+        View(flex_direction: FlexDirection::Column, width: 100pct) {
+            // Totals
+            View(
+                border_style: BorderStyle::Round,
+                border_color: Color::Cyan,
+                padding_left: 1,
+                padding_right: 1,
+                margin_bottom: 1,
+                flex_direction: FlexDirection::Column,
+            ) {
+                Text(
+                    content: format!(
+                        "Token usage - {} tokens in {} responses across {} sessions",
+                        format_tokens(total.total()),
+                        stats.responses,
+                        stats.sessions.len(),
+                    ),
+                    color: Color::Cyan,
+                    weight: Weight::Bold,
+                )
+                Text(
+                    content: format!(
+                        "Input {} · Output {} · Cache write {} · Cache read {} · Web searches {}",
+                        format_tokens(total.input),
+                        format_tokens(total.output),
+                        format_tokens(total.cache_creation),
+                        format_tokens(total.cache_read),
+                        total.web_search_requests,
+                    ),
+                )
+                Text(
+                    content: format!(
+                        "Cache hit ratio {} · Estimated cost {}{}",
+                        format_percent(total.cache_hit_ratio()),
+                        format_cost(stats.cost),
+                        if stats.unpriced > 0 {
+                            format!(" ({} responses from unpriced models)", stats.unpriced)
+                        } else {
+                            String::new()
+                        },
+                    ),
+                    color: Color::Green,
+                )
+                #((budget.session.is_some() || budget.day.is_some()).then(|| element! {
+                    Text(
+                        content: format!(
+                            "Budget {} per session ({} over) · {} per day ({} over)",
+                            budget.session.map_or("-".to_string(), format_cost),
+                            stats.sessions_over_budget(),
+                            budget.day.map_or("-".to_string(), format_cost),
+                            stats.days_over_budget(),
+                        ),
+                        color: if over_budget > 0 { Color::Red } else { Color::Green },
+                    )
+                }))
+            }
+
+            // Daily volume
+            View(
+                border_style: BorderStyle::Round,
+                border_color: Color::DarkGrey,
+                padding_left: 1,
+                padding_right: 1,
+                margin_bottom: 1,
+                flex_direction: FlexDirection::Column,
+            ) {
+                Text(
+                    content: match (volume.first(), volume.last()) {
+                        (Some((from, _)), Some((to, _))) => format!("Daily tokens {} – {}", from, to),
+                        _ => "Daily tokens - no usage recorded".to_string(),
+                    },
+                    weight: Weight::Bold,
+                )
+                Text(content: sparkline(&values), color: Color::Blue)
+                #(peak.map(|(day, tokens)| element! {
+                    Text(content: format!("Peak {} tokens on {}", format_tokens(*tokens), day), color: Color::Grey)
+                }))
+            }
+
+            // Breakdowns
+            View(flex_direction: FlexDirection::Row, width: 100pct) {
+                UsageTable(title: "Projects", rows: stats.projects.clone(), limit: props.rows, name_width: 25u16)
+                UsageTable(title: "Models", rows: stats.models.clone(), limit: props.rows, name_width: 31u16)
+            }
+            View(flex_direction: FlexDirection::Row, width: 100pct) {
+                UsageTable(
+                    title: "Sessions",
+                    rows: stats.sessions.clone(),
+                    limit: props.rows,
+                    name_width: 25u16,
+                    budget: budget.session,
+                )
+                UsageTable(title: "Days", rows: stats.day_rows(), limit: props.rows, name_width: 11u16, budget: budget.day)
+                UsageTable(title: "Months", rows: stats.month_rows(), limit: props.rows, name_width: 8u16)
+            }
+        }
+    }
+}
+
+#[derive(Default, Props)]
+struct UsageTableProps {
+    title: String,
+    rows: Vec<UsageRow>,
+    limit: Option<usize>,
+    /// Columns of the name column. Widths are fixed so printed tables line
+    /// up without a terminal to size them against.
+    name_width: u16,
+    /// Cost above which a row is shown in red.
+    budget: Option<f64>,
+}
+
+#[component]
+fn UsageTable(props: &UsageTableProps) -> impl Into<AnyElement<'static>> {
+    let shown = props.limit.unwrap_or(props.rows.len()).min(props.rows.len());
+
+    element! {
+        View(
+            flex_direction: FlexDirection::Column,
+            border_style: BorderStyle::Round,
+            border_color: Color::Grey,
+            padding_left: 1,
+            padding_right: 1,
+        ) {
+            Text(
+                content: if shown < props.rows.len() {
+                    format!("{} (top {} of {})", props.title, shown, props.rows.len())
+                } else {
+                    props.title.clone()
+                },
+                weight: Weight::Bold,
+            )
+            View(border_style: BorderStyle::Single, border_edges: Edges::Bottom, border_color: Color::DarkGrey) {
+                View(width: props.name_width) {
+                    Text(content: "")
+                }
+                View(width: 7) {
+                    Text(content: "Tokens", color: Color::Grey)
+                }
+                View(width: 7) {
+                    Text(content: "Hit", color: Color::Grey)
+                }
+                View(width: 9) {
+                    Text(content: "Cost", color: Color::Grey)
+                }
+            }
+            #(props.rows.iter().take(shown).map(|row| {
+                let over = props.budget.is_some_and(|limit| row.cost > limit);
+                element! {
+                    View {
+                        View(width: props.name_width, height: 1, overflow: Overflow::Hidden) {
+                            Text(content: &row.name, color: if over { Color::Red } else { Color::Reset })
+                        }
+                        View(width: 7) {
+                            Text(content: format_tokens(row.tokens.total()), color: Color::Cyan)
+                        }
+                        View(width: 7) {
+                            Text(content: format_percent(row.tokens.cache_hit_ratio()), color: Color::Green)
+                        }
+                        View(width: 9) {
+                            Text(
+                                content: format_cost(row.cost),
+                                color: if over { Color::Red } else { Color::Yellow },
+                                weight: if over { Weight::Bold } else { Weight::Normal },
+                            )
+                        }
+                    }
+                }
+            }))
+        }
+    }
+}
@@ -0,0 +1,105 @@
+use claude_projects_viewer::cache::IndexCache;
+use claude_projects_viewer::export;
+use claude_projects_viewer::parser::ProjectParser;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// A fresh directory under the system temp dir, removed before use.
+fn scratch_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("claude-projects-viewer-{}-{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn entry_names(dir: &Path) -> Vec<String> {
+    let mut names: Vec<String> = fs::read_dir(dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+        .filter(|name| name.ends_with(".json"))
+        .collect();
+    names.sort();
+    names
+}
+
+#[test]
+fn prune_removes_entries_that_cannot_be_hit() {
+    let root = scratch_dir("prune");
+    let sessions = root.join("projects");
+    let cache = IndexCache::new(root.join("cache"));
+    fs::create_dir_all(&sessions).unwrap();
+    let fixture = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/assistant_old.jsonl");
+    fs::copy(&fixture, sessions.join("kept.jsonl")).unwrap();
+    fs::copy(&fixture, sessions.join("deleted.jsonl")).unwrap();
+
+    let entries = ProjectParser::discover_project_files(std::slice::from_ref(&sessions)).unwrap();
+    assert_eq!(entries.len(), 2);
+    for entry in &entries {
+        ProjectParser::load_entry(entry, Some(&cache)).unwrap();
+    }
+    // Left behind by an older naming scheme or cache version
+    fs::write(cache.dir().join("00000000deadbeef.json"), r#"{"version":1,"path":"/nowhere"}"#).unwrap();
+    fs::write(cache.dir().join("0123456789abcdef.json"), "not json").unwrap();
+    assert_eq!(entry_names(cache.dir()).len(), 4);
+
+    fs::remove_file(sessions.join("deleted.jsonl")).unwrap();
+    assert_eq!(cache.prune().unwrap(), 3);
+    assert_eq!(entry_names(cache.dir()).len(), 1);
+
+    // The surviving entry is still hit, and a second sweep waits a day
+    let kept = entries.iter().find(|entry| entry.path.ends_with("kept.jsonl")).unwrap();
+    assert!(cache.get(kept).is_some());
+    fs::write(cache.dir().join("00000000deadbeef.json"), "stale").unwrap();
+    assert_eq!(cache.prune().unwrap(), 0);
+
+    fs::remove_dir_all(root).unwrap();
+}
+
+#[test]
+fn entry_names_do_not_depend_on_the_toolchain() {
+    let root = scratch_dir("names");
+    let cache = IndexCache::new(root.join("cache"));
+    let session = root.join("session.jsonl");
+    fs::copy(Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/assistant_old.jsonl"), &session).unwrap();
+
+    let entries = ProjectParser::discover_project_files(std::slice::from_ref(&root)).unwrap();
+    ProjectParser::load_entry(&entries[0], Some(&cache)).unwrap();
+
+    // FNV-1a of the path bytes
+    let bytes = session.as_os_str().as_encoded_bytes();
+    let mut hash = 0xcbf2_9ce4_8422_2325_u64;
+    for &byte in bytes {
+        hash = (hash ^ byte as u64).wrapping_mul(0x0100_0000_01b3);
+    }
+    assert_eq!(entry_names(cache.dir()), vec![format!("{:016x}.json", hash)]);
+
+    fs::remove_dir_all(root).unwrap();
+}
+
+#[test]
+fn reloading_a_cached_session_keeps_its_title() {
+    let root = scratch_dir("titles");
+    let sessions = root.join("projects");
+    let cache = IndexCache::new(root.join("cache"));
+    fs::create_dir_all(&sessions).unwrap();
+    let fixtures = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/split_summary");
+    for entry in fs::read_dir(&fixtures).unwrap() {
+        let entry = entry.unwrap();
+        fs::copy(entry.path(), sessions.join(entry.file_name())).unwrap();
+    }
+
+    let entries = ProjectParser::discover_project_files(std::slice::from_ref(&sessions)).unwrap();
+    ProjectParser::load_projects(&entries, 1, Some(&cache));
+    // Now every session comes from the cache, without its records
+    let projects = ProjectParser::load_projects(&entries, 1, Some(&cache));
+    let first = projects.iter().find(|project| project.session_id() == "1a2b3c4d-first").unwrap();
+    assert!(!first.is_loaded());
+
+    // Its summary is in another file, which the reload does not read
+    let loaded = export::with_records(&Arc::new(first.clone())).unwrap();
+    assert!(loaded.is_loaded());
+    assert_eq!(loaded.title.as_deref(), Some("CI build failure from a stale lockfile"));
+
+    fs::remove_dir_all(root).unwrap();
+}
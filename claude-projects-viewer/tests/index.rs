@@ -0,0 +1,122 @@
+use claude_projects_viewer::index::SearchIndex;
+use claude_projects_viewer::query::Query;
+use claude_projects_viewer::types::{MessageText, ProjectFile, Scope, Scopes, SearchResult};
+use serde_json::json;
+use std::sync::Arc;
+
+/// A session whose messages each hold the given sections.
+fn project(name: &str, messages: &[&[(Scope, &str)]]) -> Arc<ProjectFile> {
+    let mut project = ProjectFile::new(format!("{name}.jsonl"));
+    project.texts = messages
+        .iter()
+        .map(|sections| {
+            let (scopes, content): (Vec<Scope>, Vec<&str>) = sections.iter().copied().unzip();
+            serde_json::from_value::<MessageText>(json!({ "kind": "user", "content": content, "scopes": scopes })).unwrap()
+        })
+        .collect();
+    Arc::new(project)
+}
+
+/// A session of plain text messages.
+fn texts(name: &str, messages: &[&str]) -> Arc<ProjectFile> {
+    let messages: Vec<Vec<(Scope, &str)>> = messages.iter().map(|text| vec![(Scope::Text, *text)]).collect();
+    let messages: Vec<&[(Scope, &str)]> = messages.iter().map(Vec::as_slice).collect();
+    project(name, &messages)
+}
+
+fn search(index: &SearchIndex, query: &str, scopes: Scopes) -> Vec<SearchResult> {
+    index.search(&Query::parse(query).unwrap(), scopes)
+}
+
+/// Indices of the messages of a single-session index matching `query`.
+fn matching(index: &SearchIndex, query: &str) -> Vec<usize> {
+    search(index, query, Scopes::ALL)
+        .first()
+        .map(|result| result.message_indices.clone())
+        .unwrap_or_default()
+}
+
+fn score_of(result: &SearchResult, message: usize) -> f32 {
+    let at = result.message_indices.iter().position(|&i| i == message).unwrap();
+    result.scores[at]
+}
+
+#[test]
+fn more_occurrences_rank_higher() {
+    let index = SearchIndex::build(&[texts("a", &["rust one two", "rust rust rust"])]);
+    let results = search(&index, "rust", Scopes::ALL);
+
+    assert_eq!(results[0].message_indices, vec![0, 1]);
+    assert!(score_of(&results[0], 1) > score_of(&results[0], 0));
+}
+
+#[test]
+fn rarer_terms_rank_higher() {
+    let common = texts("common", &["shared alpha", "shared beta", "shared gamma"]);
+    let rare = texts("rare", &["unusual delta"]);
+    let index = SearchIndex::build(&[common, rare]);
+    let results = search(&index, "shared OR unusual", Scopes::ALL);
+
+    let names: Vec<&str> = results.iter().map(|result| result.project.path.as_str()).collect();
+    assert_eq!(names, vec!["rare.jsonl", "common.jsonl"]);
+    assert!(results[0].score() > results[1].score());
+}
+
+#[test]
+fn words_match_by_prefix_but_phrases_need_exact_order() {
+    let index = SearchIndex::build(&[texts("a", &["foo bar baz", "bar foo", "football"])]);
+
+    assert_eq!(matching(&index, "fo"), vec![0, 1, 2]);
+    assert_eq!(matching(&index, "\"foo bar\""), vec![0]);
+    // Words inside a phrase match exactly, not by prefix
+    assert_eq!(matching(&index, "\"fo bar\""), Vec::<usize>::new());
+    // Words joined by punctuation form a phrase
+    assert_eq!(matching(&index, "foo-bar"), vec![0]);
+}
+
+#[test]
+fn phrases_do_not_span_content_blocks() {
+    let index = SearchIndex::build(&[project("a", &[&[(Scope::Text, "ends with foo"), (Scope::Text, "bar starts")]])]);
+
+    assert_eq!(matching(&index, "foo bar"), vec![0]);
+    assert_eq!(matching(&index, "\"foo bar\""), Vec::<usize>::new());
+}
+
+#[test]
+fn negations_combine_with_and_and_or() {
+    let index = SearchIndex::build(&[texts("a", &["foo", "foo bar", "bar", "baz"])]);
+
+    assert_eq!(matching(&index, "foo -bar"), vec![0]);
+    assert_eq!(matching(&index, "-foo -bar"), vec![3]);
+    assert_eq!(matching(&index, "foo OR -bar"), vec![0, 1, 3]);
+    assert_eq!(matching(&index, "-(foo OR bar)"), vec![3]);
+    assert_eq!(matching(&index, "(foo -bar) OR baz"), vec![0, 3]);
+}
+
+#[test]
+fn scopes_limit_where_terms_are_found() {
+    let index = SearchIndex::build(&[project(
+        "a",
+        &[
+            &[(Scope::Text, "visible answer"), (Scope::Thinking, "hidden reasoning")],
+            &[(Scope::Text, "hidden in plain text")],
+        ],
+    )]);
+    let mut text_only = Scopes::NONE;
+    text_only.insert(Scope::Text);
+    let mut thinking_only = Scopes::NONE;
+    thinking_only.insert(Scope::Thinking);
+
+    let results = search(&index, "reasoning", text_only);
+    assert!(results.is_empty());
+    let results = search(&index, "\"hidden reasoning\"", text_only);
+    assert!(results.is_empty());
+
+    let results = search(&index, "hidden", text_only);
+    assert_eq!(results[0].message_indices, vec![1]);
+
+    let results = search(&index, "hidden", thinking_only);
+    assert_eq!(results[0].message_indices, vec![0]);
+    assert!(results[0].scopes[0].contains(Scope::Thinking));
+    assert!(!results[0].scopes[0].contains(Scope::Text));
+}
@@ -0,0 +1,74 @@
+use claude_projects_viewer::matcher::{fuzzy_match, Matcher, SearchMode};
+use std::ops::Range;
+
+fn fuzzy(pattern: &str, text: &str) -> Option<Vec<Range<usize>>> {
+    let pattern: Vec<char> = pattern.chars().collect();
+    fuzzy_match(&pattern, text).map(|(_, ranges)| ranges)
+}
+
+fn matched<'a>(text: &'a str, ranges: &[Range<usize>]) -> Vec<&'a str> {
+    ranges.iter().map(|range| &text[range.clone()]).collect()
+}
+
+#[test]
+fn fuzzy_allows_one_typo_per_four_characters() {
+    // "parser" has six characters, so one may be missing
+    assert_eq!(matched("parsr", &fuzzy("parser", "parsr").unwrap()), vec!["parsr"]);
+    assert_eq!(fuzzy("parser", "prsr"), None);
+    // Eight characters allow two
+    assert!(fuzzy("snapshot", "snapsht").is_some());
+    assert!(fuzzy("snapshot", "snpsht").is_some());
+    assert_eq!(fuzzy("snapshot", "snpst"), None);
+}
+
+#[test]
+fn fuzzy_gaps_are_limited() {
+    assert_eq!(fuzzy("ab", "a--------b"), Some(vec![0..1, 9..10]));
+    assert_eq!(fuzzy("ab", "a---------b"), None);
+}
+
+#[test]
+fn fuzzy_matches_stay_within_one_line() {
+    assert_eq!(fuzzy("ab", "a\nb"), None);
+    // Offsets on later lines are offsets into the whole text
+    let text = "first line\nsecond ab";
+    let ranges = fuzzy("ab", text).unwrap();
+    assert_eq!(ranges.len(), 1);
+    assert_eq!(ranges[0], 18..20);
+}
+
+#[test]
+fn fuzzy_ranges_are_byte_ranges_on_multibyte_text() {
+    let text = "こんにちは日本語";
+    let ranges = fuzzy("日本", text).unwrap();
+    assert_eq!(ranges.len(), 1);
+    assert_eq!(ranges[0], 15..21);
+    assert_eq!(matched(text, &ranges), vec!["日本"]);
+
+    let text = "🦀 Rust crab";
+    let ranges = fuzzy("rust", text).unwrap();
+    assert_eq!(matched(text, &ranges), vec!["Rust"]);
+
+    // Highlighting through the matcher slices the same way
+    let matcher = Matcher::new(SearchMode::Fuzzy, "日本 rust").unwrap();
+    let text = "日本の🦀 Rust";
+    assert_eq!(matched(text, &matcher.find(text)), vec!["日本", "Rust"]);
+}
+
+#[test]
+fn regex_is_case_sensitive_only_with_uppercase() {
+    let lower = Matcher::new(SearchMode::Regex, "rust").unwrap();
+    assert_eq!(lower.find("Rust and rust"), vec![0..4, 9..13]);
+
+    let upper = Matcher::new(SearchMode::Regex, "Rust").unwrap();
+    assert_eq!(matched("Rust and rust", &upper.find("Rust and rust")), vec!["Rust"]);
+    assert_eq!(upper.find("rust"), Vec::<Range<usize>>::new());
+
+    // Escapes are not uppercase text
+    let escaped = Matcher::new(SearchMode::Regex, r"foo\S+").unwrap();
+    assert_eq!(matched("FOObar", &escaped.find("FOObar")), vec!["FOObar"]);
+    let class = Matcher::new(SearchMode::Regex, r"\p{Greek} \pL x").unwrap();
+    assert_eq!(matched("α b X", &class.find("α b X")), vec!["α b X"]);
+
+    assert!(Matcher::new(SearchMode::Regex, "(unclosed").is_err());
+}
@@ -0,0 +1,188 @@
+use claude_projects_viewer::parser::ProjectParser;
+use claude_projects_viewer::types::{Content, ProjectFile, SessionMessage, ToolResultContent, ToolResultItem, UserContent};
+use std::path::Path;
+
+fn load_fixture(name: &str) -> ProjectFile {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures").join(name);
+    ProjectParser::load_project_file(&path).expect("fixture should be readable")
+}
+
+fn assistant_messages(project: &ProjectFile) -> Vec<&SessionMessage> {
+    project
+        .messages
+        .iter()
+        .filter(|message| message.get_type() == "assistant")
+        .collect()
+}
+
+#[test]
+fn old_assistant_shape_is_not_dropped() {
+    let project = load_fixture("assistant_old.jsonl");
+
+    assert!(project.diagnostics.is_empty(), "{:?}", project.diagnostics);
+    assert_eq!(project.messages.len(), 3);
+
+    let assistants = assistant_messages(&project);
+    assert_eq!(assistants.len(), 2);
+
+    // No id, no model and no cache counters at all
+    let SessionMessage::Assistant { message, .. } = assistants[0] else {
+        unreachable!()
+    };
+    assert_eq!(message.id, None);
+    assert_eq!(message.model, None);
+    let usage = message.usage.as_ref().unwrap();
+    assert_eq!(usage.input_tokens, Some(12));
+    assert_eq!(usage.output_tokens, Some(4));
+    assert_eq!(usage.cache_creation_input_tokens, None);
+    assert_eq!(usage.cache_read_input_tokens, None);
+
+    // Only some of the cache counters
+    let SessionMessage::Assistant { message, .. } = assistants[1] else {
+        unreachable!()
+    };
+    assert_eq!(message.model.as_deref(), Some("claude-3-7-sonnet-20250219"));
+    let usage = message.usage.as_ref().unwrap();
+    assert_eq!(usage.cache_read_input_tokens, Some(100));
+    assert_eq!(usage.cache_creation_input_tokens, None);
+}
+
+#[test]
+fn new_assistant_shape_keeps_extra_fields() {
+    let project = load_fixture("assistant_new.jsonl");
+
+    assert!(project.diagnostics.is_empty(), "{:?}", project.diagnostics);
+    let assistants = assistant_messages(&project);
+    assert_eq!(assistants.len(), 1);
+
+    let SessionMessage::Assistant { message, .. } = assistants[0] else {
+        unreachable!()
+    };
+    assert_eq!(message.id.as_deref(), Some("msg_new"));
+    let usage = message.usage.as_ref().unwrap();
+    assert_eq!(usage.cache_creation_input_tokens, Some(500));
+    assert_eq!(usage.service_tier.as_deref(), Some("standard"));
+
+    let server_tool_use = usage.server_tool_use.as_ref().unwrap();
+    assert_eq!(server_tool_use.web_search_requests, Some(2));
+    assert!(server_tool_use.extra.contains_key("web_fetch_requests"));
+    assert!(usage.extra.contains_key("cache_creation"));
+    assert!(message.extra.contains_key("container"));
+}
+
+#[test]
+fn new_assistant_shape_round_trips() {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/assistant_new.jsonl");
+    let original = std::fs::read_to_string(path).unwrap();
+
+    for line in original.lines() {
+        let message: SessionMessage = serde_json::from_str(line).unwrap();
+        let expected: serde_json::Value = serde_json::from_str(line).unwrap();
+        let actual = serde_json::to_value(&message).unwrap();
+        if message.get_type() == "assistant" {
+            assert_eq!(actual["message"], expected["message"]);
+        }
+    }
+}
+
+#[test]
+fn unsigned_thinking_and_mixed_tool_results_are_kept() {
+    let project = load_fixture("mixed_blocks.jsonl");
+
+    assert!(project.diagnostics.is_empty(), "{:?}", project.diagnostics);
+    assert_eq!(project.messages.len(), 3);
+
+    let SessionMessage::Assistant { message, .. } = &project.messages[1] else {
+        unreachable!()
+    };
+    let Content::Thinking { signature, .. } = &message.content()[0] else {
+        unreachable!()
+    };
+    assert_eq!(signature, &None);
+
+    // Text, an image and a block type we don't know, in one result
+    let SessionMessage::User { message, .. } = &project.messages[2] else {
+        unreachable!()
+    };
+    let UserContent::Array(blocks) = &message.content else {
+        unreachable!()
+    };
+    let Content::ToolResult { content: Some(ToolResultContent::Array(items)), .. } = &blocks[0] else {
+        unreachable!()
+    };
+    assert!(matches!(items[0], ToolResultItem::Text { .. }));
+    assert!(matches!(items[1], ToolResultItem::Image { .. }));
+    assert!(matches!(&items[2], ToolResultItem::Unknown { kind, .. } if kind == "document"));
+    assert!(project.texts[2].content.iter().any(|text| text == "Rendered /dashboard"));
+}
+
+#[test]
+fn records_round_trip_unchanged() {
+    // Fields an older log leaves out must not come back as defaults
+    for fixture in ["unknown_fields.jsonl", "assistant_old.jsonl", "tool_results.jsonl", "mixed_blocks.jsonl"] {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures").join(fixture);
+        let original = std::fs::read_to_string(path).unwrap();
+
+        for line in original.lines() {
+            let message: SessionMessage = serde_json::from_str(line).unwrap();
+            let expected: serde_json::Value = serde_json::from_str(line).unwrap();
+            assert_eq!(serde_json::to_value(&message).unwrap(), expected, "{} in {}", message.get_type(), fixture);
+        }
+    }
+}
+
+#[test]
+fn tool_inputs_keep_their_key_order() {
+    let project = load_fixture("tool_results.jsonl");
+    assert!(project.diagnostics.is_empty(), "{:?}", project.diagnostics);
+
+    // Value equality ignores key order, so compare the keys as written
+    let actual = serde_json::to_value(&project.messages[1]).unwrap();
+    let keys: Vec<&str> = actual["message"]["content"][0]["input"]
+        .as_object()
+        .unwrap()
+        .keys()
+        .map(String::as_str)
+        .collect();
+    assert_eq!(keys, vec!["file_path", "old_string", "new_string", "replace_all"]);
+    assert_eq!(project.messages[1].get_git_branch(), None);
+}
+
+#[test]
+fn summaries_title_the_session_holding_their_leaf() {
+    // The summary of the first session was written at the top of the second
+    // session's file, as Claude Code does when a conversation continues
+    let root = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/split_summary");
+    let entries = ProjectParser::discover_project_files(&[root]).unwrap();
+    let projects = ProjectParser::load_projects(&entries, 1, None);
+    let title = |id: &str| {
+        let project = projects.iter().find(|project| project.session_id() == id).unwrap();
+        project.title.as_deref()
+    };
+
+    assert_eq!(title("1a2b3c4d-first"), Some("CI build failure from a stale lockfile"));
+    assert_eq!(title("5e6f7a8b-second"), Some("Now add a release workflow"));
+    // A file of summaries keeps only the one that belongs to no loaded session
+    assert_eq!(title("9c0d1e2f-summaries"), Some("Session that was never loaded"));
+
+    // On its own, the first session falls back to its prompt
+    let first = load_fixture("split_summary/1a2b3c4d-first.jsonl");
+    assert_eq!(first.title.as_deref(), Some("Why does the build fail on CI?"));
+}
+
+#[test]
+fn discovery_takes_roots_literally_and_lists_each_file_once() {
+    let root = std::env::temp_dir().join(format!("claude-projects-viewer-[discover]*-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&root);
+    let nested = root.join("-Users-foo-src-app");
+    std::fs::create_dir_all(&nested).unwrap();
+    let fixture = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/assistant_old.jsonl");
+    std::fs::copy(&fixture, nested.join("session.jsonl")).unwrap();
+
+    // The root and one of its own subdirectories
+    let entries = ProjectParser::discover_project_files(&[root.clone(), nested.clone()]).unwrap();
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0].path, root.join("-Users-foo-src-app/session.jsonl"));
+
+    std::fs::remove_dir_all(root).unwrap();
+}
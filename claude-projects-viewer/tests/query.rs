@@ -0,0 +1,132 @@
+use claude_projects_viewer::query::{Field, Filter, Query};
+use claude_projects_viewer::types::{MessageText, ProjectFile};
+use serde_json::json;
+
+fn term(word: &str) -> Query {
+    Query::Term(word.to_string())
+}
+
+fn parse_error(input: &str) -> String {
+    Query::parse(input).expect_err(input)
+}
+
+/// A message with only the given fields set.
+fn message(fields: serde_json::Value) -> MessageText {
+    let mut record = json!({ "kind": "user", "content": [] });
+    record.as_object_mut().unwrap().extend(fields.as_object().unwrap().clone());
+    serde_json::from_value(record).unwrap()
+}
+
+fn filter(input: &str) -> Filter {
+    match Query::parse(input).unwrap() {
+        Query::Filter(filter) => filter,
+        query => panic!("{input} parsed as {query:?}"),
+    }
+}
+
+#[test]
+fn or_binds_looser_than_and() {
+    assert_eq!(
+        Query::parse("a b OR c").unwrap(),
+        Query::Or(vec![Query::And(vec![term("a"), term("b")]), term("c")]),
+    );
+    assert_eq!(Query::parse("a | b").unwrap(), Query::Or(vec![term("a"), term("b")]));
+}
+
+#[test]
+fn negation_applies_to_groups_but_not_inside_words() {
+    assert_eq!(
+        Query::parse("-(a OR b)").unwrap(),
+        Query::Not(Box::new(Query::Or(vec![term("a"), term("b")]))),
+    );
+    assert_eq!(Query::parse("a-b").unwrap(), term("a-b"));
+    assert_eq!(
+        Query::parse("x -y").unwrap(),
+        Query::And(vec![term("x"), Query::Not(Box::new(term("y")))]),
+    );
+}
+
+#[test]
+fn phrases_and_quoted_filter_values() {
+    assert_eq!(
+        Query::parse("tool:\"Bash\"").unwrap(),
+        Query::Filter(Filter::Text(Field::Tool, "bash".to_string())),
+    );
+    assert_eq!(
+        Query::parse("branch:\"feature x\" y").unwrap(),
+        Query::And(vec![
+            Query::Filter(Filter::Text(Field::Branch, "feature x".to_string())),
+            term("y"),
+        ]),
+    );
+    // An unclosed quote runs to the end of the query
+    assert_eq!(Query::parse("\"foo bar").unwrap(), Query::Phrase("foo bar".to_string()));
+    // Only known field names make a filter
+    assert_eq!(Query::parse("http://x").unwrap(), term("http://x"));
+}
+
+#[test]
+fn empty_queries_match_everything() {
+    assert!(Query::parse("").unwrap().is_empty());
+    assert!(Query::parse("   ").unwrap().is_empty());
+    assert!(!Query::parse("a").unwrap().is_empty());
+}
+
+#[test]
+fn malformed_queries_are_rejected() {
+    assert_eq!(parse_error("(a"), "missing ')'");
+    assert_eq!(parse_error("a )"), "unexpected ')'");
+    assert_eq!(parse_error("-"), "'-' must be followed by a term");
+    assert_eq!(parse_error("foo OR"), "'OR' must be followed by a term");
+    assert_eq!(parse_error("OR foo"), "'OR' must follow a term");
+    assert_eq!(parse_error("a | | b"), "'OR' must be followed by a term");
+    assert_eq!(parse_error("(a OR)"), "'OR' must be followed by a term");
+    assert_eq!(parse_error("()"), "empty group");
+    assert_eq!(parse_error("a -( ) b"), "empty group");
+    assert_eq!(parse_error("after:"), "after: needs a value");
+    assert_eq!(parse_error("after:2024-13-01"), "invalid date: 2024-13-01 (expected YYYY-MM-DD)");
+}
+
+#[test]
+fn date_filters_include_after_and_exclude_before() {
+    let project = ProjectFile::new("session.jsonl".to_string());
+    let midnight = message(json!({ "time": "2024-03-01T00:00:00Z" }));
+    let just_before = message(json!({ "time": "2024-02-29T23:59:59Z" }));
+    let undated = message(json!({}));
+
+    let after = filter("after:2024-03-01");
+    assert!(after.matches(&project, &midnight));
+    assert!(!after.matches(&project, &just_before));
+    assert!(!after.matches(&project, &undated));
+
+    let before = filter("before:2024-03-01");
+    assert!(!before.matches(&project, &midnight));
+    assert!(before.matches(&project, &just_before));
+    assert!(!before.matches(&project, &undated));
+
+    let precise = filter("after:2024-02-29T23:59:59Z");
+    assert!(precise.matches(&project, &just_before));
+}
+
+#[test]
+fn text_filters_ignore_case() {
+    let project = ProjectFile::new("session.jsonl".to_string());
+    let text = message(json!({
+        "kind": "assistant",
+        "session_id": "ABCdef12-3456",
+        "git_branch": "Main",
+        "model": "claude-Sonnet-4",
+        "tools": ["Bash"],
+    }));
+
+    assert!(filter("session:abc").matches(&project, &text));
+    assert!(filter("session:ABCDEF12").matches(&project, &text));
+    // A prefix of the id, not any part of it
+    assert!(!filter("session:def").matches(&project, &text));
+
+    assert!(filter("TYPE:Assistant").matches(&project, &text));
+    assert!(filter("tool:bash").matches(&project, &text));
+    assert!(filter("branch:main").matches(&project, &text));
+    assert!(!filter("branch:mai").matches(&project, &text));
+    assert!(filter("model:sonnet").matches(&project, &text));
+}
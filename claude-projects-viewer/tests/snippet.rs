@@ -0,0 +1,96 @@
+use claude_projects_viewer::snippet::{extract, Snippet};
+use std::ops::Range;
+
+/// Byte ranges of every occurrence of `words` in `text`, sorted.
+fn find(text: &str, words: &[&str]) -> Vec<Range<usize>> {
+    let mut ranges: Vec<Range<usize>> = words
+        .iter()
+        .flat_map(|word| text.match_indices(word).map(|(i, found)| i..i + found.len()))
+        .collect();
+    ranges.sort_by_key(|range| range.start);
+    ranges
+}
+
+fn highlighted(snippet: &Snippet) -> Vec<&str> {
+    snippet.highlights.iter().map(|range| &snippet.text[range.clone()]).collect()
+}
+
+#[test]
+fn overlapping_windows_share_a_snippet() {
+    let text = "one two three four five six seven eight nine ten";
+    let snippets = extract(text, &find(text, &["four", "six"]), 5);
+    assert_eq!(snippets.len(), 1);
+    assert_eq!(snippets[0].text, "...hree four five six seve...");
+    assert_eq!(highlighted(&snippets[0]), vec!["four", "six"]);
+
+    let snippets = extract(text, &find(text, &["two", "nine"]), 4);
+    let texts: Vec<&str> = snippets.iter().map(|snippet| snippet.text.as_str()).collect();
+    assert_eq!(texts, vec!["one two thr...", "...ght nine ten"]);
+    assert_eq!(highlighted(&snippets[0]), vec!["two"]);
+    assert_eq!(highlighted(&snippets[1]), vec!["nine"]);
+}
+
+#[test]
+fn ellipses_mark_only_the_sides_that_were_cut() {
+    let text = "needle in a haystack";
+    assert_eq!(extract(text, &find(text, &["needle"]), 3)[0].text, "needle in...");
+    // The cut fell just before a space, which shows that it is between words
+    assert_eq!(extract(text, &find(text, &["haystack"]), 3)[0].text, "... a haystack");
+    assert_eq!(extract(text, &find(text, &["in"]), 20)[0].text, text);
+
+    assert_eq!(Snippet::leading("short", 10).text, "short");
+    assert_eq!(Snippet::leading("a longer text", 8).text, "a longer...");
+}
+
+#[test]
+fn highlights_can_touch_the_cut() {
+    let text = "xx match yy";
+    // No context at all: the window is exactly the match
+    let snippet = &extract(text, &find(text, &["match"]), 0)[0];
+    assert_eq!(snippet.text, "...match...");
+    assert_eq!(highlighted(snippet), vec!["match"]);
+
+    // Matches at the very start and end of the text
+    let text = "start middle end";
+    let snippets = extract(text, &find(text, &["start", "end"]), 1);
+    assert_eq!(snippets.len(), 2);
+    assert_eq!(snippets[0].text, "start...");
+    assert_eq!(snippets[1].text, "... end");
+    assert_eq!(highlighted(&snippets[0]), vec!["start"]);
+    assert_eq!(highlighted(&snippets[1]), vec!["end"]);
+}
+
+#[test]
+fn whitespace_runs_collapse_to_one_space() {
+    let text = "first   line\n\n\tsecond  line";
+    let snippet = &extract(text, &find(text, &["second"]), 40)[0];
+    assert_eq!(snippet.text, "first line second line");
+    assert_eq!(highlighted(snippet), vec!["second"]);
+
+    // Whitespace right before a cut is dropped rather than left dangling
+    let text = "word    and more";
+    let snippet = &extract(text, &find(text, &["word"]), 3)[0];
+    assert_eq!(snippet.text, "word...");
+    assert_eq!(highlighted(snippet), vec!["word"]);
+
+    // A match that is itself spread over a line break
+    let text = "end of\nline";
+    let snippet = &extract(text, &find(text, &["of\nline"]), 10)[0];
+    assert_eq!(snippet.text, "end of line");
+    assert_eq!(highlighted(snippet), vec!["of line"]);
+}
+
+#[test]
+fn context_counts_characters_not_bytes() {
+    let text = "今日はいい天気ですね、散歩に行きましょう";
+    let snippet = &extract(text, &find(text, &["天気"]), 2)[0];
+    assert_eq!(snippet.text, "...いい天気です...");
+    assert_eq!(highlighted(snippet), vec!["天気"]);
+
+    let text = "🦀🦀 crab 🦀🦀";
+    let snippet = &extract(text, &find(text, &["crab"]), 2)[0];
+    assert_eq!(snippet.text, "...🦀 crab 🦀...");
+    assert_eq!(highlighted(snippet), vec!["crab"]);
+
+    assert_eq!(Snippet::leading("日本語のテキスト", 3).text, "日本語...");
+}